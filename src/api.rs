@@ -1,76 +1,490 @@
 use cfg_if::cfg_if;
 
+#[cfg(feature = "ssr")]
+pub mod attachments;
+#[cfg(feature = "ssr")]
+pub mod conversations;
+#[cfg(feature = "ssr")]
+pub mod export;
+#[cfg(feature = "ssr")]
+pub mod import;
+#[cfg(feature = "ssr")]
+pub mod knowledge;
+#[cfg(feature = "ssr")]
+pub mod openai;
+#[cfg(feature = "ssr")]
+pub mod personas;
+#[cfg(feature = "ssr")]
+pub mod search;
+#[cfg(feature = "ssr")]
+pub mod shares;
+#[cfg(feature = "ssr")]
+pub mod store;
+#[cfg(feature = "ssr")]
+pub mod templates;
+#[cfg(feature = "ssr")]
+pub mod tools;
+#[cfg(feature = "ssr")]
+pub mod vision;
+
 cfg_if! {
     if #[cfg(feature = "ssr")] {
         use actix_web::{web, HttpRequest, HttpResponse, Error};
         use actix_web::web::Payload;
         use actix_ws::Message as Msg;
         use futures::StreamExt;
-        use std::sync::Arc;
+        use std::collections::HashMap;
+        use std::sync::{Arc, Mutex};
         use std::io::Error as StdErr;
-        use std::io::ErrorKind as StdErrKind;
         use ollama_rs::Ollama;
         use ollama_rs::generation::chat::{ChatMessage, MessageRole};
         use ollama_rs::generation::chat::request::ChatMessageRequest;
         use std::env;
+        use std::time::{Duration, Instant};
         use dotenv::dotenv;
+        use tokio::sync::{mpsc, OwnedMappedMutexGuard, OwnedMutexGuard};
+        use uuid::Uuid;
+        use crate::model::attachment::Attachment;
+        use crate::model::conversation::{Conversation, Message, MessageMetadata, Role};
+        use crate::model::event::{ChatEvent, ChatRequest, ClientMessage, CommandRequest, SessionCommand};
+        use crate::model::knowledge::Citation;
+        use crate::model::tool::ToolStep;
+        use tools::{CallFilter, ToolRegistry};
+        use crate::model::persona::{GenerationSettings, Persona};
+        use store::Store;
+
+        /// How long a session nobody uses stays in memory. Evicted sessions are rebuilt from storage.
+        const SESSION_IDLE: Duration = Duration::from_secs(30 * 60);
+
+        /// Live chat sessions keyed by conversation id, shared by the WebSocket and SSE transports.
+        #[derive(Default)]
+        pub struct Sessions(Mutex<HashMap<Uuid, LiveSession>>);
+
+        /// A conversation's slot in `Sessions`, empty until its session is first loaded.
+        struct LiveSession {
+            session: Arc<tokio::sync::Mutex<Option<Session>>>,
+            last_used: Instant,
+        }
+
+        /// A session locked for the length of one turn or command.
+        type SessionGuard = OwnedMappedMutexGuard<Option<Session>, Session>;
 
+        /// A conversation's history together with the model and options of its persona.
+        struct Session {
+            model: String,
+            options: GenerationSettings,
+            chat_history: Vec<ChatMessage>,
+            /// How many of the first `chat_history` messages are the system prompt and the
+            /// persona's example turns, which precede the conversation itself.
+            setup_len: usize,
+            /// Names of the tools the persona lets the model call.
+            tools: Vec<String>,
+            /// The CSV and TSV files attached so far, which the `sql` tool queries.
+            tables: Vec<Attachment>,
+            /// Whether `model` accepts images, looked up the first time a prompt carries one.
+            vision: Option<bool>,
+        }
+
+        impl Sessions {
+            /// Locks the conversation's session, waiting while another request holds it, so
+            /// turns on one conversation run one after the other. The session is rebuilt from
+            /// storage when the conversation is not live (e.g. after a server restart or once
+            /// evicted). New conversations start with `persona_id`, stored ones with their own persona.
+            async fn lock(&self, id: Uuid, persona_id: Option<Uuid>, store: &Store) -> std::io::Result<SessionGuard> {
+                let slot = {
+                    let mut live = self.0.lock().unwrap();
+                    let now = Instant::now();
+                    live.retain(|_, slot| {
+                        Arc::strong_count(&slot.session) > 1 || now.duration_since(slot.last_used) < SESSION_IDLE
+                    });
+                    let slot = live.entry(id).or_insert_with(|| LiveSession { session: Default::default(), last_used: now });
+                    slot.last_used = now;
+                    slot.session.clone()
+                };
+
+                let mut session = slot.lock_owned().await;
+                if session.is_none() {
+                    *session = Some(load_session(id, persona_id, store)?);
+                }
+                Ok(OwnedMutexGuard::map(session, |session| session.as_mut().expect("loaded above")))
+            }
+        }
+
+        /// Builds a conversation's session from its persona and stored messages.
+        fn load_session(id: Uuid, persona_id: Option<Uuid>, store: &Store) -> std::io::Result<Session> {
+            let conversation = store.conversations().get(id)?;
+            let persona_id = match &conversation {
+                Some(conversation) => conversation.persona_id,
+                None => persona_id,
+            };
+            let mut session = session_setup(&personas::resolve(store, persona_id)?);
+            if let Some(conversation) = conversation {
+                session.tables.extend(conversation.messages.iter()
+                    .flat_map(|m| m.attachments.iter().filter(|a| a.is_table()).cloned()));
+                session.chat_history.extend(conversation.messages.into_iter().map(|m| {
+                    let role = match m.role {
+                        Role::System => MessageRole::System,
+                        Role::User => MessageRole::User,
+                        Role::Assistant => MessageRole::Assistant,
+                    };
+                    if m.attachments.is_empty() {
+                        return Ok(ChatMessage::new(role, m.content));
+                    }
+                    let content = attachments::prompt_with_attachments(store, &m.content, &m.attachments);
+                    let images = attachments::images(store, &m.attachments)?;
+                    Ok(match images.is_empty() {
+                        true => ChatMessage::new(role, content),
+                        false => ChatMessage::new(role, content).with_images(images),
+                    })
+                }).collect::<std::io::Result<Vec<_>>>()?);
+            }
+            Ok(session)
+        }
+
+        /// Streams the model's answer to `user_message`. With tools enabled, a reply ending
+        /// in a tool call is not the answer yet: the tool is run, its result handed back and
+        /// the model asked again, until it answers without calling one.
+        ///
+        /// The answer's generation stats are gathered from the final chunk of each reply;
+        /// tokens and generation time add up over the replies of a tool-using answer.
+        ///
+        /// `sources` retrieved from a knowledge base go with this turn's requests only; the
+        /// history keeps the plain prompt, so later turns don't carry them along.
         async fn infer(
             ollama: Arc<Ollama>,
-            chat_history: &mut Vec<ChatMessage>,
-            user_message: &String,
-            tx: tokio::sync::mpsc::Sender<String>
-        ) -> Result<Option<String>, Error> {
-            chat_history.push(ChatMessage::new(MessageRole::User, user_message.clone()));
-            dotenv().ok();
-            let model_name = env::var("OLLAMA_MODEL_NAME").expect("OLLAMA_MODEL_NAME Env Var must be set");
-            let chat_req = ChatMessageRequest::new(model_name.to_string(), chat_history.clone());
+            session: &mut Session,
+            registry: &ToolRegistry,
+            user_message: ChatMessage,
+            sources: &[(Citation, String)],
+            tx: mpsc::Sender<ChatEvent>
+        ) -> Result<(String, Vec<ToolStep>, MessageMetadata), Error> {
+            let prompt_index = session.chat_history.len();
+            session.chat_history.push(user_message);
+            let mut answer = String::new();
+            let mut steps = Vec::new();
+            let started = Instant::now();
+            let mut metadata = MessageMetadata::default();
+
+            for round in 0..=tools::MAX_ROUNDS {
+                // The last round goes without tools so the model has to answer.
+                let use_tools = !registry.is_empty() && round < tools::MAX_ROUNDS;
+                let mut messages = session.chat_history.clone();
+                let prompt = &mut messages[prompt_index];
+                prompt.content = knowledge::prompt_with_sources(&prompt.content, sources);
+                // Text-only models reject images, so they only ever see the prompts' text.
+                if session.vision != Some(true) {
+                    messages.iter_mut().for_each(|m| m.images = None);
+                }
+                if use_tools {
+                    match messages.first_mut() {
+                        Some(system) if system.role == MessageRole::System => {
+                            system.content = format!("{}\n\n{}", system.content, registry.instructions());
+                        }
+                        _ => messages.insert(0, ChatMessage::new(MessageRole::System, registry.instructions())),
+                    }
+                }
+                let chat_req = ChatMessageRequest::new(session.model.clone(), messages)
+                    .options(personas::generation_options(&session.options));
+
+                let stream = ollama.send_chat_messages_stream(chat_req).await
+                    .map_err(|e| Error::from(StdErr::other(format!("Ollama error: {:?}", e))))?;
+
+                let mut response = String::new();
+                let mut filter = CallFilter::default();
+
+                futures::pin_mut!(stream);
+
+                while let Some(res) = stream.next().await {
+                    let res = res.map_err(|_| Error::from(StdErr::other("Stream error")))?;
+
+                    if let Some(msg) = res.message {
+                        response.push_str(&msg.content);
+                        let visible = if use_tools { filter.push(&msg.content) } else { msg.content };
+                        send_token(&tx, &mut answer, visible).await?;
+                        if metadata.time_to_first_token_ms.is_none() && !answer.is_empty() {
+                            metadata.time_to_first_token_ms = Some(started.elapsed().as_millis() as u64);
+                        }
+                    }
+
+                    if let Some(final_data) = res.final_data {
+                        let tokens = u64::from(final_data.eval_count);
+                        metadata.prompt_tokens = Some(u64::from(final_data.prompt_eval_count));
+                        metadata.completion_tokens = Some(metadata.completion_tokens.unwrap_or_default() + tokens);
+                        metadata.generation_duration_ms = Some(
+                            metadata.generation_duration_ms.unwrap_or_default() + final_data.eval_duration / 1_000_000
+                        );
+                        // Ollama 0.1.9's chunks don't say why generation stopped, but running into
+                        // the token limit is the one reason other than the model stopping itself.
+                        let limit = session.options.num_predict.filter(|limit| *limit > 0);
+                        let hit_limit = limit.is_some_and(|limit| tokens >= limit as u64);
+                        metadata.finish_reason = Some(if hit_limit { "length" } else { "stop" }.to_string());
+                        break;
+                    }
+                }
+                send_token(&tx, &mut answer, filter.finish()).await?;
+
+                session.chat_history.push(ChatMessage::new(MessageRole::Assistant, response.clone()));
+
+                let Some(call) = tools::parse_call(&response).filter(|_| use_tools) else { break };
+                let step = registry.run(call).await;
+                session.chat_history.push(ChatMessage::new(MessageRole::User, tools::result_message(&step)));
+                tx.send(ChatEvent::ToolStep { step: step.clone() }).await
+                    .map_err(|_| Error::from(StdErr::other("Anything Error")))?;
+                steps.push(step);
+            }
+
+            metadata.total_duration_ms = Some(started.elapsed().as_millis() as u64);
+            Ok((answer, steps, metadata))
+        }
+
+        async fn send_token(tx: &mpsc::Sender<ChatEvent>, answer: &mut String, content: String) -> Result<(), Error> {
+            if content.is_empty() {
+                return Ok(());
+            }
+            answer.push_str(&content);
+            tx.send(ChatEvent::Token { content }).await
+                .map_err(|_| Error::from(StdErr::other("Anything Error")))
+        }
+
+        /// Runs one prompt against the conversation's history and reports the outcome
+        /// as a terminating `Done` or `Error` event.
+        async fn respond(
+            ollama: Arc<Ollama>,
+            sessions: Arc<Sessions>,
+            store: Arc<Store>,
+            request: ChatRequest,
+            tx: mpsc::Sender<ChatEvent>
+        ) {
+            let mut session = match sessions.lock(request.conversation_id, request.persona_id, &store).await {
+                Ok(session) => session,
+                Err(e) => {
+                    let _ = tx.send(ChatEvent::Error { message: format!("Failed to load conversation: {e}") }).await;
+                    return;
+                }
+            };
+            let attached = match attachments::load(&store, &request.attachments) {
+                Ok(attached) => attached,
+                Err(e) => {
+                    let _ = tx.send(ChatEvent::Error { message: format!("Failed to load attachments: {e}") }).await;
+                    return;
+                }
+            };
+            let sources = match request.knowledge_base_id {
+                Some(id) => match knowledge::retrieve(&store, &ollama, id, &request.content).await {
+                    Ok(sources) => sources,
+                    Err(e) => {
+                        let _ = tx.send(ChatEvent::Error { message: format!("Failed to search the knowledge base: {e}") }).await;
+                        return;
+                    }
+                },
+                None => Vec::new(),
+            };
+            let citations: Vec<Citation> = sources.iter().map(|(citation, _)| citation.clone()).collect();
+            if !citations.is_empty() {
+                let _ = tx.send(ChatEvent::Citations { citations: citations.clone() }).await;
+            }
 
-            let stream = ollama.send_chat_messages_stream(chat_req).await
-                .map_err(|e| Error::from(StdErr::new(StdErrKind::Other, format!("Ollama error: {:?}", e))))?;
+            let prompt = attachments::prompt_with_attachments(&store, &request.content, &attached);
+            let mut user_message = ChatMessage::new(MessageRole::User, prompt);
+            match attachments::images(&store, &attached) {
+                Ok(images) if images.is_empty() => {}
+                Ok(images) => user_message = user_message.with_images(images),
+                Err(e) => {
+                    let _ = tx.send(ChatEvent::Error { message: format!("Failed to load attachments: {e}") }).await;
+                    return;
+                }
+            }
 
-            let mut response = String::new();
+            let has_images = user_message.images.is_some() || session.chat_history.iter().any(|m| m.images.is_some());
+            if has_images && session.vision.is_none() {
+                session.vision = Some(vision::supports_images(&ollama, &session.model).await);
+            }
+            if user_message.images.is_some() && session.vision == Some(false) {
+                let message = format!("{} can't see images, so only the text of this message was sent.", session.model);
+                let _ = tx.send(ChatEvent::Notice { message }).await;
+            }
+            let history_len = session.chat_history.len();
+            let tables_len = session.tables.len();
+            session.tables.extend(attached.iter().filter(|a| a.is_table()).cloned());
+            let mut registry = ToolRegistry::with(&session.tools);
+            if let Err(e) = registry.add_tables(&store, &session.tables) {
+                session.tables.truncate(tables_len);
+                let _ = tx.send(ChatEvent::Error { message: format!("Failed to load attachments: {e}") }).await;
+                return;
+            }
 
-            futures::pin_mut!(stream);
+            let event = match infer(ollama, &mut session, &registry, user_message, &sources, tx.clone()).await {
+                Ok((reply, tool_steps, metadata)) => {
+                    let metadata = MessageMetadata { model: Some(session.model.clone()), ..metadata };
+                    let _ = tx.send(ChatEvent::Stats { metadata: metadata.clone() }).await;
+                    match record_exchange(&store, &request, attached, citations, tool_steps, reply, metadata) {
+                        Ok(()) => ChatEvent::Done,
+                        Err(e) => ChatEvent::Error { message: format!("Failed to save conversation: {e}") },
+                    }
+                }
+                Err(e) => {
+                    // Drop the dangling user turn so a retry starts from a clean history.
+                    session.chat_history.truncate(history_len);
+                    session.tables.truncate(tables_len);
+                    ChatEvent::Error { message: e.to_string() }
+                }
+            };
 
-            while let Some(res) = stream.next().await {
-                let res = res.map_err(|_| Error::from(StdErr::new(StdErrKind::Other, "Stream error")))?;
+            let _ = tx.send(event).await;
+        }
 
-                if let Some(msg) = res.message {
-                    response.push_str(&msg.content);
+        /// Appends a prompt and its reply to the stored conversation, creating it on the first exchange.
+        fn record_exchange(
+            store: &Store,
+            request: &ChatRequest,
+            attachments: Vec<Attachment>,
+            citations: Vec<Citation>,
+            tool_steps: Vec<ToolStep>,
+            reply: String,
+            metadata: MessageMetadata
+        ) -> std::io::Result<()> {
+            let conversations = store.conversations();
+            let mut conversation = conversations.get(request.conversation_id)?.unwrap_or_else(|| Conversation {
+                id: request.conversation_id,
+                name: conversation_name(&request.content),
+                messages: Vec::new(),
+                persona_id: request.persona_id,
+                knowledge_base_id: None,
+            });
+            conversation.knowledge_base_id = request.knowledge_base_id;
 
-                    tx.send(msg.content).await.map_err(|_| Error::from(StdErr::new(StdErrKind::Other, "Anything Error")))?;
+            let prompt = Message { attachments, ..Message::new(Role::User, request.content.clone()) };
+            let metadata = MessageMetadata { parent_id: Some(prompt.id), ..metadata };
+            conversation.messages.push(prompt);
+            conversation.messages.push(Message { citations, tool_steps, metadata, ..Message::new(Role::Assistant, reply) });
+
+            conversations.put(conversation.id, &conversation)
+        }
+
+        /// Applies a session command and describes the outcome as a `Notice`.
+        async fn run_command(sessions: &Sessions, store: &Store, request: CommandRequest) -> ChatEvent {
+            let mut session = match sessions.lock(request.conversation_id, request.persona_id, store).await {
+                Ok(session) => session,
+                Err(e) => return ChatEvent::Error { message: format!("Failed to load conversation: {e}") },
+            };
+
+            let message = match request.command {
+                SessionCommand::Model { model: None } => format!("Model: {}", session.model),
+                SessionCommand::Model { model: Some(model) } => {
+                    session.model = model;
+                    session.vision = None;
+                    format!("Model set to {}", session.model)
+                }
+                SessionCommand::System { prompt: None } => {
+                    let prompt = session.chat_history.first().map(|m| m.content.as_str()).unwrap_or_default();
+                    format!("System prompt: {prompt}")
                 }
+                SessionCommand::System { prompt: Some(prompt) } => {
+                    match session.chat_history.first_mut() {
+                        Some(system) if system.role == MessageRole::System => system.content = prompt,
+                        _ => {
+                            session.chat_history.insert(0, ChatMessage::new(MessageRole::System, prompt));
+                            session.setup_len += 1;
+                        }
+                    }
+                    "System prompt updated".to_string()
+                }
+                SessionCommand::Temperature { value: None } => match session.options.temperature {
+                    Some(temperature) => format!("Temperature: {temperature}"),
+                    None => "Temperature: model default".to_string(),
+                },
+                SessionCommand::Temperature { value: Some(value) } => {
+                    session.options.temperature = Some(value);
+                    format!("Temperature set to {value}")
+                }
+                SessionCommand::Clear => {
+                    let setup_len = session.setup_len;
+                    session.chat_history.truncate(setup_len);
+                    session.tables.clear();
+                    let stored = store.conversations().get(request.conversation_id).ok().flatten();
 
-                if res.final_data.is_some() {
-                    break;
+                    if let Some(mut conversation) = stored {
+                        conversation.messages.clear();
+                        if let Err(e) = store.conversations().put(conversation.id, &conversation) {
+                            return ChatEvent::Error { message: format!("Failed to save conversation: {e}") };
+                        }
+                    }
+                    "Conversation cleared".to_string()
+                }
+            };
+
+            ChatEvent::Notice { message }
+        }
+
+        /// Handles one client message, streaming its events into `tx`.
+        async fn dispatch(
+            ollama: Arc<Ollama>,
+            sessions: Arc<Sessions>,
+            store: Arc<Store>,
+            message: ClientMessage,
+            tx: mpsc::Sender<ChatEvent>
+        ) {
+            match message {
+                ClientMessage::Chat(request) => respond(ollama, sessions, store, request, tx).await,
+                ClientMessage::Command(request) => {
+                    let _ = tx.send(run_command(&sessions, &store, request).await).await;
                 }
             }
+        }
 
-            chat_history.push(ChatMessage::new(MessageRole::Assistant, response.clone()));
+        /// Names a new conversation after the first line of its opening prompt.
+        fn conversation_name(prompt: &str) -> String {
+            let line = prompt.lines().next().unwrap_or_default().trim();
+            match line.char_indices().nth(48) {
+                Some((end, _)) => format!("{}…", &line[..end]),
+                None => line.to_string(),
+            }
+        }
 
-            Ok(Some(response))
+        /// Default model used when a request does not name one.
+        fn model_name() -> String {
+            dotenv().ok();
+            env::var("OLLAMA_MODEL_NAME").expect("OLLAMA_MODEL_NAME Env Var must be set")
         }
 
-        fn session_setup() -> Vec<ChatMessage> {
+        /// System prompt of the default persona and of OpenAI-compatible requests without one.
+        fn system_prompt() -> String {
             dotenv().ok();
-            let persona = env::var("OLLAMA_SYSTEM_PROMPT").expect("OLLAMA_SYSTEM_PROMPT Env Var must be set");
-            
-            vec![
-                ChatMessage::new(MessageRole::System, persona.to_string()),
-                ChatMessage::new(MessageRole::User, "Hello there?".to_string()),
-                ChatMessage::new(MessageRole::Assistant, "Hello - How may I help you today?".to_string()),
-            ]
+            env::var("OLLAMA_SYSTEM_PROMPT").expect("OLLAMA_SYSTEM_PROMPT Env Var must be set")
         }
 
-        pub async fn ws(req: HttpRequest, body: Payload, ollama: web::Data<Ollama>) -> Result<HttpResponse, Error> {
-            use std::sync::Mutex;
-            use tokio::sync::mpsc;
+        /// Starts a session from a persona: its system prompt followed by its example turns.
+        fn session_setup(persona: &Persona) -> Session {
+            let mut chat_history = vec![ChatMessage::new(MessageRole::System, persona.system_prompt.clone())];
+            for example in &persona.examples {
+                chat_history.push(ChatMessage::new(MessageRole::User, example.user.clone()));
+                chat_history.push(ChatMessage::new(MessageRole::Assistant, example.assistant.clone()));
+            }
+
+            Session {
+                model: persona.model.clone().filter(|m| !m.is_empty()).unwrap_or_else(model_name),
+                options: persona.options.clone(),
+                setup_len: chat_history.len(),
+                chat_history,
+                tools: persona.tools.clone(),
+                tables: Vec::new(),
+                vision: None,
+            }
+        }
 
+        pub async fn ws(
+            req: HttpRequest,
+            body: Payload,
+            ollama: web::Data<Ollama>,
+            sessions: web::Data<Sessions>,
+            store: web::Data<Store>
+        ) -> Result<HttpResponse, Error> {
             let (response, session, mut msg_stream) = actix_ws::handle(&req, body)?;
 
             let (send_inference, mut receive_inference) = mpsc::channel(100);
             let ollama_instance: Arc<Ollama> = ollama.into_inner().clone();
+            let sessions: Arc<Sessions> = sessions.into_inner();
+            let store: Arc<Store> = store.into_inner();
             let sess = Arc::new(Mutex::new(session));
 
             let sess_clone = sess.clone();
@@ -78,12 +492,11 @@ cfg_if! {
                 let (send_new_user_message, mut receive_new_user_message) = mpsc::channel(100);
 
                 let ollama_clone = Arc::clone(&ollama_instance);
+                let send_error = send_inference.clone();
 
                 actix_rt::spawn(async move {
-                    let mut chat_history = session_setup();
-
-                    while let Some(new_user_message) = receive_new_user_message.recv().await {
-                        let _ = infer(ollama_clone.clone(), &mut chat_history, &new_user_message, send_inference.clone()).await;
+                    while let Some(message) = receive_new_user_message.recv().await {
+                        dispatch(ollama_clone.clone(), sessions.clone(), store.clone(), message, send_inference.clone()).await;
                     }
                 });
 
@@ -95,21 +508,51 @@ cfg_if! {
                                 return;
                             }
                         }
-                        Msg::Text(s) => {
-                            let _ = send_new_user_message.send(s.to_string()).await;
-                        }
+                        Msg::Text(s) => match serde_json::from_str::<ClientMessage>(&s) {
+                            Ok(message) => {
+                                let _ = send_new_user_message.send(message).await;
+                            }
+                            Err(e) => {
+                                let _ = send_error.send(ChatEvent::Error { message: format!("Invalid request: {e}") }).await;
+                            }
+                        },
                         _ => break,
                     }
                 }
             });
 
             actix_rt::spawn(async move {
-                while let Some(message) = receive_inference.recv().await {
+                while let Some(event) = receive_inference.recv().await {
+                    let message = serde_json::to_string(&event).expect("ChatEvent is serializable");
                     sess.lock().unwrap().text(message).await.expect("Issue sending over WebSocket");
                 }
             });
 
             Ok(response)
         }
+
+        /// Fallback for clients whose WebSocket upgrade is blocked: the message is POSTed
+        /// and the reply streams back as Server-Sent Events carrying the same `ChatEvent`s.
+        pub async fn sse(
+            message: web::Json<ClientMessage>,
+            ollama: web::Data<Ollama>,
+            sessions: web::Data<Sessions>,
+            store: web::Data<Store>
+        ) -> HttpResponse {
+            let (tx, rx) = mpsc::channel(100);
+            actix_rt::spawn(dispatch(ollama.into_inner(), sessions.into_inner(), store.into_inner(), message.into_inner(), tx));
+
+            let events = futures::stream::unfold(rx, |mut rx| async move {
+                let event = rx.recv().await?;
+                let data = serde_json::to_string(&event).expect("ChatEvent is serializable");
+                let frame = format!("event: {}\ndata: {}\n\n", event.name(), data);
+                Some((Ok::<_, Error>(web::Bytes::from(frame)), rx))
+            });
+
+            HttpResponse::Ok()
+                .content_type("text/event-stream")
+                .insert_header(("Cache-Control", "no-cache"))
+                .streaming(events)
+        }
     }
-}
\ No newline at end of file
+}
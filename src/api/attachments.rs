@@ -0,0 +1,229 @@
+//! File attachments. Text, Markdown, source code and PDF uploads are stored as-is
+//! alongside their extracted text, which is appended to the prompt the model sees.
+//! PNG, JPEG, GIF and WebP images are stored without text and sent to the model as
+//! images when it supports vision (see [`super::vision`]). Of CSV and TSV files the
+//! model only sees the first rows, as it queries them with the `sql` tool instead
+//! (see [`super::tools`]).
+//!
+//! Extracted text is cut to `RUSTIC_ATTACHMENT_CHARS` characters (20 000 by default)
+//! per file so a single upload cannot crowd the conversation out of the context window.
+
+use super::export::escape_html;
+use super::store::Store;
+use crate::model::attachment::{Attachment, CSV, TSV};
+use actix_web::error::{ErrorBadRequest, ErrorInternalServerError, ErrorNotFound, ErrorUnsupportedMediaType};
+use actix_web::http::header::{self, HeaderValue};
+use actix_web::{web, Error, HttpRequest, HttpResponse};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ollama_rs::generation::images::Image;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::io;
+use uuid::Uuid;
+
+const PDF: &str = "application/pdf";
+const TEXT: &str = "text/plain; charset=utf-8";
+/// Lines of a CSV or TSV file shown in the prompt, including the header.
+const TABLE_PREVIEW_LINES: usize = 6;
+
+#[derive(Deserialize)]
+pub struct UploadQuery {
+    name: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredAttachment {
+    #[serde(flatten)]
+    attachment: Attachment,
+    text: String,
+}
+
+fn budget() -> usize {
+    env::var("RUSTIC_ATTACHMENT_CHARS").ok().and_then(|chars| chars.parse().ok()).unwrap_or(20_000)
+}
+
+fn is_pdf(req: &HttpRequest, name: &str, bytes: &[u8]) -> bool {
+    let content_type = req.headers().get(header::CONTENT_TYPE).and_then(|v| v.to_str().ok()).unwrap_or_default();
+    content_type.starts_with(PDF) || looks_like_pdf(name, bytes)
+}
+
+pub(super) fn looks_like_pdf(name: &str, bytes: &[u8]) -> bool {
+    name.to_lowercase().ends_with(".pdf") || bytes.starts_with(b"%PDF-")
+}
+
+/// The type of a text file: CSV and TSV files by their extension, anything else plain text.
+fn text_type(name: &str) -> String {
+    let name = name.to_lowercase();
+    if name.ends_with(".csv") {
+        format!("{CSV}; charset=utf-8")
+    } else if name.ends_with(".tsv") || name.ends_with(".tab") {
+        format!("{TSV}; charset=utf-8")
+    } else {
+        TEXT.to_string()
+    }
+}
+
+/// The type of an image the models can read, told by its leading bytes rather than
+/// by the name or header the browser sent.
+fn image_type(bytes: &[u8]) -> Option<&'static str> {
+    match bytes {
+        [0x89, b'P', b'N', b'G', ..] => Some("image/png"),
+        [0xff, 0xd8, 0xff, ..] => Some("image/jpeg"),
+        [b'G', b'I', b'F', b'8', ..] => Some("image/gif"),
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => Some("image/webp"),
+        _ => None,
+    }
+}
+
+pub(super) fn extract_text(pdf: bool, bytes: &[u8]) -> Result<String, String> {
+    if pdf {
+        // The PDF parser panics on some malformed files; treat that as an extraction failure.
+        return match std::panic::catch_unwind(|| pdf_extract::extract_text_from_mem(bytes)) {
+            Ok(Ok(text)) => Ok(text),
+            Ok(Err(e)) => Err(format!("Could not read the PDF: {e}")),
+            Err(_) => Err("Could not read the PDF".to_string()),
+        };
+    }
+
+    match std::str::from_utf8(bytes) {
+        Ok(text) if !text.contains('\0') => Ok(text.to_string()),
+        _ => Err("Only text, Markdown, source code, PDF and image files are supported".to_string()),
+    }
+}
+
+/// `POST /api/attachments?name=notes.md` with the file as the request body.
+pub async fn upload(
+    req: HttpRequest,
+    query: web::Query<UploadQuery>,
+    body: web::Bytes,
+    store: web::Data<Store>,
+) -> Result<HttpResponse, Error> {
+    let name = query.name.trim().to_string();
+    if name.is_empty() {
+        return Err(ErrorBadRequest("Missing file name"));
+    }
+    if body.is_empty() {
+        return Err(ErrorBadRequest("The file is empty"));
+    }
+
+    let (content_type, text) = match image_type(&body) {
+        Some(image) => (image.to_string(), String::new()),
+        None => {
+            let pdf = is_pdf(&req, &name, &body);
+            let bytes = body.clone();
+            let text = web::block(move || extract_text(pdf, &bytes))
+                .await
+                .map_err(ErrorInternalServerError)?
+                .map_err(ErrorUnsupportedMediaType)?;
+            (if pdf { PDF.to_string() } else { text_type(&name) }, text)
+        }
+    };
+
+    let budget = budget();
+    let truncated = text.chars().count() > budget;
+    let text = if truncated { text.chars().take(budget).collect() } else { text };
+
+    let attachment = Attachment {
+        id: Uuid::new_v4(),
+        name,
+        content_type,
+        size: body.len(),
+        truncated,
+    };
+
+    store.blobs("attachments").put(attachment.id, &body).map_err(ErrorInternalServerError)?;
+    store.collection("attachments")
+        .put(attachment.id, &StoredAttachment { attachment: attachment.clone(), text })
+        .map_err(ErrorInternalServerError)?;
+
+    Ok(HttpResponse::Created().json(attachment))
+}
+
+/// `GET /api/attachments/{id}` serves the original file.
+pub async fn get(id: web::Path<Uuid>, store: web::Data<Store>) -> Result<HttpResponse, Error> {
+    let stored = store.collection::<StoredAttachment>("attachments")
+        .get(*id)
+        .map_err(ErrorInternalServerError)?
+        .ok_or_else(|| ErrorNotFound(format!("No attachment {id}")))?;
+    let bytes = store.blobs("attachments")
+        .get(*id)
+        .map_err(ErrorInternalServerError)?
+        .ok_or_else(|| ErrorNotFound(format!("No attachment {id}")))?;
+
+    // Only ever served as plain text, PDF or one of the sniffed image types, whatever was
+    // uploaded, so a file can't run as a page.
+    let content_type = match image_type(&bytes) {
+        Some(image) => image,
+        None if stored.attachment.content_type == PDF => PDF,
+        None => TEXT,
+    };
+    let file_name = stored.attachment.name.replace(['"', '\\', '\r', '\n'], "_");
+    Ok(HttpResponse::Ok()
+        .content_type(content_type)
+        .insert_header((header::X_CONTENT_TYPE_OPTIONS, HeaderValue::from_static("nosniff")))
+        .insert_header((header::CONTENT_DISPOSITION, format!("inline; filename=\"{file_name}\"")))
+        .body(bytes))
+}
+
+/// Looks up the attachments a prompt refers to, failing on unknown ids.
+pub fn load(store: &Store, ids: &[Uuid]) -> io::Result<Vec<Attachment>> {
+    let attachments = store.collection::<StoredAttachment>("attachments");
+    ids.iter()
+        .map(|id| {
+            attachments.get(*id)?
+                .map(|stored| stored.attachment)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("No attachment {id}")))
+        })
+        .collect()
+}
+
+/// The prompt as sent to the model: the user's text followed by the text of each attachment.
+pub fn prompt_with_attachments(store: &Store, content: &str, attachments: &[Attachment]) -> String {
+    let collection = store.collection::<StoredAttachment>("attachments");
+    let mut prompt = content.to_string();
+    for attachment in attachments.iter().filter(|a| !a.is_image()) {
+        let text = match collection.get(attachment.id) {
+            Ok(Some(stored)) => stored.text,
+            _ => continue,
+        };
+        let (text, note) = match attachment.is_table() {
+            true => {
+                let lines: Vec<&str> = text.trim().lines().collect();
+                match lines.len() > TABLE_PREVIEW_LINES {
+                    true => (lines[..TABLE_PREVIEW_LINES].join("\n"), "\n[Only the first rows are shown. Query the whole file with the sql tool.]"),
+                    false => (lines.join("\n"), "\n[Query the file with the sql tool.]"),
+                }
+            }
+            false if attachment.truncated => (text.trim().to_string(), "\n[The rest of the file was cut off.]"),
+            false => (text.trim().to_string(), ""),
+        };
+        prompt.push_str(&format!(
+            "\n\n<file name=\"{}\">\n{}{}\n</file>",
+            escape_html(&attachment.name), close_tags_escaped(&text), note
+        ));
+    }
+    prompt
+}
+
+/// Escapes `</file` in a file's text, so its contents can't end the `<file>` element early.
+fn close_tags_escaped(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(i) = rest.as_bytes().windows(6).position(|w| w.eq_ignore_ascii_case(b"</file")) {
+        escaped.push_str(&rest[..=i]);
+        escaped.push('\\');
+        rest = &rest[i + 1..];
+    }
+    escaped.push_str(rest);
+    escaped
+}
+
+/// The image attachments of a prompt, encoded the way Ollama expects them.
+pub fn images(store: &Store, attachments: &[Attachment]) -> io::Result<Vec<Image>> {
+    let blobs = store.blobs("attachments");
+    attachments.iter()
+        .filter(|attachment| attachment.is_image())
+        .filter_map(|attachment| blobs.get(attachment.id).transpose())
+        .map(|bytes| bytes.map(|bytes| Image::from_base64(&STANDARD.encode(bytes))))
+        .collect()
+}
@@ -0,0 +1,124 @@
+//! REST endpoints over the stored conversations.
+
+use super::export::{self, Format};
+use super::store::Store;
+use crate::model::conversation::{Conversation, ConversationSummary, Message};
+use actix_web::error::{ErrorBadRequest, ErrorInternalServerError, ErrorNotFound};
+use actix_web::http::header::{ContentDisposition, DispositionParam, DispositionType};
+use actix_web::{web, Error, HttpResponse};
+use serde::Deserialize;
+use std::cmp::Reverse;
+use uuid::Uuid;
+
+#[derive(Deserialize)]
+pub struct ExportQuery {
+    #[serde(default)]
+    format: Format,
+    /// Comma separated conversation ids; every stored conversation when omitted.
+    ids: Option<String>,
+}
+
+/// Messages a page holds when the client doesn't say, and at most.
+const PAGE_SIZE: usize = 50;
+const MAX_PAGE_SIZE: usize = 500;
+
+#[derive(Deserialize)]
+pub struct GetQuery {
+    /// Only the conversation's last messages; all of them when omitted.
+    last: Option<usize>,
+}
+
+#[derive(Deserialize)]
+pub struct MessagesQuery {
+    /// The message the page ends before; the page ends with the last message when omitted.
+    before: Option<Uuid>,
+    limit: Option<usize>,
+}
+
+/// `GET /api/conversations`
+pub async fn list(store: web::Data<Store>) -> Result<HttpResponse, Error> {
+    let conversations = recent_first(&store)?;
+    Ok(HttpResponse::Ok().json(conversations.iter().map(ConversationSummary::from).collect::<Vec<_>>()))
+}
+
+/// `GET /api/conversations/{id}?last=50`
+pub async fn get(id: web::Path<Uuid>, query: web::Query<GetQuery>, store: web::Data<Store>) -> Result<HttpResponse, Error> {
+    let mut conversation = load(&store, id.into_inner())?;
+    if let Some(last) = query.last {
+        let skipped = conversation.messages.len().saturating_sub(last);
+        conversation.messages.drain(..skipped);
+    }
+    Ok(HttpResponse::Ok().json(conversation))
+}
+
+/// `GET /api/conversations/{id}/messages?before={message_id}&limit=50`
+///
+/// A page of messages, oldest first, for loading a long conversation a bit at a time
+/// from its end.
+pub async fn messages(
+    id: web::Path<Uuid>,
+    query: web::Query<MessagesQuery>,
+    store: web::Data<Store>,
+) -> Result<HttpResponse, Error> {
+    let conversation = load(&store, id.into_inner())?;
+    let end = match query.before {
+        Some(before) => conversation.messages.iter()
+            .position(|m| m.id == before)
+            .ok_or_else(|| ErrorNotFound(format!("No message {before}")))?,
+        None => conversation.messages.len(),
+    };
+    let limit = query.limit.unwrap_or(PAGE_SIZE).min(MAX_PAGE_SIZE);
+    let page: &[Message] = &conversation.messages[end.saturating_sub(limit)..end];
+    Ok(HttpResponse::Ok().json(page))
+}
+
+/// `GET /api/conversations/{id}/export?format=markdown|json|html`
+pub async fn export_one(
+    id: web::Path<Uuid>,
+    query: web::Query<ExportQuery>,
+    store: web::Data<Store>,
+) -> Result<HttpResponse, Error> {
+    let conversation = load(&store, id.into_inner())?;
+    Ok(download(&[conversation], query.format))
+}
+
+/// `GET /api/conversations/export?format=markdown|json|html&ids=a,b`
+pub async fn export_many(query: web::Query<ExportQuery>, store: web::Data<Store>) -> Result<HttpResponse, Error> {
+    let conversations = match &query.ids {
+        Some(ids) => ids
+            .split(',')
+            .filter(|id| !id.trim().is_empty())
+            .map(|id| {
+                let id = Uuid::parse_str(id.trim()).map_err(ErrorBadRequest)?;
+                load(&store, id)
+            })
+            .collect::<Result<Vec<_>, Error>>()?,
+        None => recent_first(&store)?,
+    };
+
+    Ok(download(&conversations, query.format))
+}
+
+fn load(store: &Store, id: Uuid) -> Result<Conversation, Error> {
+    store.conversations()
+        .get(id)
+        .map_err(ErrorInternalServerError)?
+        .ok_or_else(|| ErrorNotFound(format!("No conversation {id}")))
+}
+
+/// Every stored conversation, most recently active first.
+fn recent_first(store: &Store) -> Result<Vec<Conversation>, Error> {
+    let mut conversations = store.conversations().all().map_err(ErrorInternalServerError)?;
+    conversations.sort_by_key(|c| Reverse(c.messages.last().map(|m| m.timestamp)));
+    Ok(conversations)
+}
+
+fn download(conversations: &[Conversation], format: Format) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type(format.content_type())
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename(export::file_name(conversations, format))],
+        })
+        .body(export::render(conversations, format))
+}
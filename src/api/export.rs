@@ -0,0 +1,140 @@
+//! Renders stored conversations as Markdown, JSON or a self-contained HTML page.
+
+use crate::markdown;
+use crate::model::conversation::{Conversation, Message};
+use serde::Deserialize;
+
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    #[default]
+    #[serde(alias = "md")]
+    Markdown,
+    Json,
+    Html,
+}
+
+impl Format {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Format::Markdown => "text/markdown; charset=utf-8",
+            Format::Json => "application/json",
+            Format::Html => "text/html; charset=utf-8",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Markdown => "md",
+            Format::Json => "json",
+            Format::Html => "html",
+        }
+    }
+}
+
+/// Renders the conversations in `format`. A single conversation exports as one
+/// JSON object, several as an array.
+pub fn render(conversations: &[Conversation], format: Format) -> String {
+    match format {
+        Format::Markdown => to_markdown(conversations),
+        Format::Json if conversations.len() == 1 => {
+            serde_json::to_string_pretty(&conversations[0]).expect("Conversation is serializable")
+        }
+        Format::Json => serde_json::to_string_pretty(conversations).expect("Conversation is serializable"),
+        Format::Html => to_html(conversations),
+    }
+}
+
+/// Suggested download name, e.g. `my-conversation.md`.
+pub fn file_name(conversations: &[Conversation], format: Format) -> String {
+    let stem = match conversations {
+        [conversation] => slug(&conversation.name),
+        _ => "conversations".to_string(),
+    };
+    let stem = if stem.is_empty() { "conversation".to_string() } else { stem };
+    format!("{stem}.{}", format.extension())
+}
+
+fn to_markdown(conversations: &[Conversation]) -> String {
+    let mut out = String::new();
+    for conversation in conversations {
+        out.push_str(&format!("# {}\n\n", conversation.name));
+        for message in &conversation.messages {
+            out.push_str(&format!("## {} · {}\n\n", message.role.label(), timestamp_label(message)));
+            out.push_str(message.content.trim_end());
+            out.push_str("\n\n");
+        }
+    }
+    out
+}
+
+fn to_html(conversations: &[Conversation]) -> String {
+    let title = match conversations {
+        [conversation] => escape_html(&conversation.name),
+        _ => "Conversations".to_string(),
+    };
+
+    let mut body = String::new();
+    for conversation in conversations {
+        body.push_str(&format!("<section>\n<h1>{}</h1>\n", escape_html(&conversation.name)));
+        for message in &conversation.messages {
+            body.push_str(&format!(
+                "<article class=\"message {}\">\n<header><strong>{}</strong> <time>{}</time></header>\n<div class=\"markdown-body\">{}</div>\n</article>\n",
+                message.role.as_str(),
+                message.role.label(),
+                timestamp_label(message),
+                markdown::render(&message.content),
+            ));
+        }
+        body.push_str("</section>\n");
+    }
+
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n<title>{title}</title>\n<style>{HTML_STYLE}{HIGHLIGHT_STYLE}</style>\n</head>\n<body>\n{body}</body>\n</html>\n"
+    )
+}
+
+/// Colors of the highlighted code blocks, shared with the app's stylesheet.
+const HIGHLIGHT_STYLE: &str = include_str!("../../style/_highlight.scss");
+
+const HTML_STYLE: &str = "
+body { max-width: 56rem; margin: 2rem auto; padding: 0 1rem; font-family: system-ui, sans-serif; color: #374151; line-height: 1.6; }
+h1 { font-size: 1.5rem; }
+.message { border-bottom: 1px solid #e5e7eb; padding: 1rem 0; }
+.message header { font-size: 0.875rem; color: #6b7280; margin-bottom: 0.5rem; }
+.message.user header strong { color: #4f46e5; }
+.message.assistant header strong { color: #059669; }
+pre { background: #f3f4f6; padding: 0.75rem 1rem; border-radius: 0.5rem; overflow: auto; }
+code { font-family: ui-monospace, monospace; font-size: 0.875em; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #d1d5db; padding: 0.25rem 0.5rem; }
+.code-toolbar { font-size: 0.75rem; color: #6b7280; margin-bottom: -0.5rem; }
+.code-toolbar button { display: none; }
+@media (prefers-color-scheme: dark) {
+  body { background: #1f2937; color: #f3f4f6; }
+  .message { border-color: #374151; }
+  pre { background: #111827; }
+}
+";
+
+fn timestamp_label(message: &Message) -> String {
+    message.timestamp.format("%Y-%m-%d %H:%M UTC").to_string()
+}
+
+fn slug(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+        .collect::<String>()
+        .split('-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
@@ -0,0 +1,368 @@
+//! Importers for the conversation exports of other chat apps.
+//!
+//! Both ChatGPT's `conversations.json` and Open WebUI's JSON export store each chat as a
+//! tree of messages, where regenerating or editing a turn starts a new branch. Every
+//! root-to-leaf path becomes its own `Conversation`: the branch the app was showing keeps
+//! the chat's title and counts as branch 1, the others are suffixed with `(branch 2)`,
+//! `(branch 3)` and so on. Messages get ids derived from their conversation and node, so
+//! a message shared by several branches is stored once per branch under different ids.
+
+use super::store::Store;
+use crate::model::conversation::{Conversation, Message, Role};
+use chrono::DateTime;
+use actix_web::error::{ErrorBadRequest, ErrorInternalServerError};
+use actix_web::{web, Error, HttpResponse};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Source {
+    #[serde(alias = "openai")]
+    ChatGpt,
+    #[serde(alias = "open-webui")]
+    OpenWebUi,
+}
+
+#[derive(Deserialize)]
+pub struct ImportQuery {
+    /// Detected from the payload when omitted.
+    source: Option<Source>,
+    #[serde(default)]
+    dry_run: bool,
+}
+
+/// What an import created, or would create when run as a dry run.
+#[derive(Serialize, Debug)]
+pub struct ImportReport {
+    pub source: Source,
+    pub dry_run: bool,
+    pub conversations: Vec<ImportedConversation>,
+    pub skipped: Vec<SkippedChat>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ImportedConversation {
+    pub id: Uuid,
+    pub name: String,
+    pub message_count: usize,
+    /// `None` for the branch the source app was showing.
+    pub branch: Option<usize>,
+    /// Re-importing the same export overwrites instead of duplicating.
+    pub replaces_existing: bool,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SkippedChat {
+    pub title: String,
+    pub reason: String,
+}
+
+const CYCLE: &str = "The message tree has a cycle";
+
+/// A chat of either format, normalised to a message tree.
+struct Chat {
+    source_id: String,
+    title: String,
+    nodes: HashMap<String, Node>,
+    current: Option<String>,
+}
+
+struct Node {
+    parent: Option<String>,
+    children: Vec<String>,
+    message: Option<Message>,
+}
+
+/// `POST /api/import?source=chatgpt|openwebui&dry_run=true`
+pub async fn import(
+    query: web::Query<ImportQuery>,
+    body: web::Bytes,
+    store: web::Data<Store>,
+) -> Result<HttpResponse, Error> {
+    let payload: Value = serde_json::from_slice(&body).map_err(ErrorBadRequest)?;
+    let source = match query.source {
+        Some(source) => source,
+        None => detect(&payload).ok_or_else(|| ErrorBadRequest("Unrecognised export format, pass ?source="))?,
+    };
+
+    let (conversations, skipped) = conversations(&payload, source);
+    let conversations_store = store.conversations();
+    let mut report = ImportReport { source, dry_run: query.dry_run, conversations: Vec::new(), skipped };
+
+    for (branch, conversation) in conversations {
+        let replaces_existing = conversations_store.get(conversation.id).map_err(ErrorInternalServerError)?.is_some();
+        if !query.dry_run {
+            conversations_store.put(conversation.id, &conversation).map_err(ErrorInternalServerError)?;
+        }
+        report.conversations.push(ImportedConversation {
+            id: conversation.id,
+            name: conversation.name,
+            message_count: conversation.messages.len(),
+            branch,
+            replaces_existing,
+        });
+    }
+
+    Ok(HttpResponse::Ok().json(report))
+}
+
+/// Turns an export into conversations, each with its branch number (`None` for the branch
+/// the source app was showing), and the chats that could not be imported.
+pub fn conversations(payload: &Value, source: Source) -> (Vec<(Option<usize>, Conversation)>, Vec<SkippedChat>) {
+    let (chats, mut skipped) = match source {
+        Source::ChatGpt => parse_chats(payload, parse_chatgpt),
+        Source::OpenWebUi => parse_chats(payload, parse_open_webui),
+    };
+
+    let mut conversations = Vec::new();
+    for chat in chats {
+        let title = chat.title.clone();
+        match chat.into_conversations(source) {
+            Ok(branches) if branches.is_empty() => {
+                skipped.push(SkippedChat { title, reason: "No user or assistant messages".to_string() });
+            }
+            Ok(branches) => conversations.extend(branches),
+            Err(reason) => skipped.push(SkippedChat { title, reason }),
+        }
+    }
+    (conversations, skipped)
+}
+
+fn detect(payload: &Value) -> Option<Source> {
+    let first = match payload {
+        Value::Array(chats) => chats.first()?,
+        chat => chat,
+    };
+    if first.get("mapping").is_some() {
+        Some(Source::ChatGpt)
+    } else if first.get("chat").is_some() {
+        Some(Source::OpenWebUi)
+    } else {
+        None
+    }
+}
+
+/// Applies `parse` to every chat of an export, which may be a single chat or an array of them.
+fn parse_chats(payload: &Value, parse: fn(&Value) -> Result<Chat, String>) -> (Vec<Chat>, Vec<SkippedChat>) {
+    let items = match payload {
+        Value::Array(items) => items.iter().collect::<Vec<_>>(),
+        item => vec![item],
+    };
+
+    let mut chats = Vec::new();
+    let mut skipped = Vec::new();
+    for item in items {
+        match parse(item) {
+            Ok(chat) => chats.push(chat),
+            Err(reason) => skipped.push(SkippedChat {
+                title: item["title"].as_str().unwrap_or_default().to_string(),
+                reason,
+            }),
+        }
+    }
+    (chats, skipped)
+}
+
+fn parse_chatgpt(item: &Value) -> Result<Chat, String> {
+    let mapping = item["mapping"].as_object().ok_or("Missing `mapping`")?;
+    let created = item["create_time"].as_f64().unwrap_or_default() as i64;
+
+    let nodes = mapping
+        .iter()
+        .map(|(id, node)| {
+            let message = &node["message"];
+            let text = message["content"]["parts"]
+                .as_array()
+                .map(|parts| parts.iter().filter_map(Value::as_str).collect::<Vec<_>>().join("\n"))
+                .or_else(|| message["content"]["text"].as_str().map(str::to_string))
+                .unwrap_or_default();
+            let timestamp = message["create_time"].as_f64().map_or(created, |t| t as i64);
+
+            (id.clone(), Node {
+                parent: node["parent"].as_str().map(str::to_string),
+                children: string_array(&node["children"]),
+                message: chat_message(message["author"]["role"].as_str(), text, timestamp),
+            })
+        })
+        .collect();
+
+    Ok(Chat {
+        source_id: item["conversation_id"].as_str().or(item["id"].as_str()).ok_or("Missing conversation id")?.to_string(),
+        title: item["title"].as_str().unwrap_or("Imported conversation").to_string(),
+        nodes,
+        current: item["current_node"].as_str().map(str::to_string),
+    })
+}
+
+fn parse_open_webui(item: &Value) -> Result<Chat, String> {
+    let chat = &item["chat"];
+    let created = item["created_at"].as_i64().or(chat["timestamp"].as_i64()).unwrap_or_default();
+    let history = chat["history"]["messages"].as_object();
+
+    let nodes = match history {
+        Some(history) => history
+            .iter()
+            .map(|(id, message)| {
+                (id.clone(), Node {
+                    parent: message["parentId"].as_str().map(str::to_string),
+                    children: string_array(&message["childrenIds"]),
+                    message: chat_message(
+                        message["role"].as_str(),
+                        message["content"].as_str().unwrap_or_default().to_string(),
+                        message["timestamp"].as_i64().unwrap_or(created),
+                    ),
+                })
+            })
+            .collect(),
+        // Older exports only carry the visible thread as a flat list.
+        None => {
+            let messages = chat["messages"].as_array().ok_or("Missing `chat.history` and `chat.messages`")?;
+            let mut nodes = HashMap::new();
+            for (i, message) in messages.iter().enumerate() {
+                nodes.insert(i.to_string(), Node {
+                    parent: i.checked_sub(1).map(|p| p.to_string()),
+                    children: if i + 1 < messages.len() { vec![(i + 1).to_string()] } else { Vec::new() },
+                    message: chat_message(
+                        message["role"].as_str(),
+                        message["content"].as_str().unwrap_or_default().to_string(),
+                        message["timestamp"].as_i64().unwrap_or(created),
+                    ),
+                });
+            }
+            nodes
+        }
+    };
+
+    Ok(Chat {
+        source_id: item["id"].as_str().ok_or("Missing chat id")?.to_string(),
+        title: item["title"].as_str().or(chat["title"].as_str()).unwrap_or("Imported conversation").to_string(),
+        nodes,
+        current: chat["history"]["currentId"].as_str().map(str::to_string),
+    })
+}
+
+/// Keeps the user and assistant turns that carry text; system prompts and tool
+/// traffic are not part of a rustic-ai conversation.
+fn chat_message(role: Option<&str>, content: String, timestamp: i64) -> Option<Message> {
+    let role = match role? {
+        "user" => Role::User,
+        "assistant" => Role::Assistant,
+        _ => return None,
+    };
+    if content.trim().is_empty() {
+        return None;
+    }
+
+    Some(Message {
+        timestamp: DateTime::from_timestamp(timestamp, 0).unwrap_or_default(),
+        ..Message::new(role, content)
+    })
+}
+
+fn string_array(value: &Value) -> Vec<String> {
+    value.as_array()
+        .map(|items| items.iter().filter_map(Value::as_str).map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+impl Chat {
+    /// One conversation per root-to-leaf path, the main branch first. Fails on a
+    /// malformed tree whose links form a cycle.
+    ///
+    /// The main branch is the one shown when the chat was exported. When the export
+    /// doesn't say which, or that branch is cut off from the root, it is the longest
+    /// intact branch, and of equally long ones the newest.
+    fn into_conversations(self, source: Source) -> Result<Vec<(Option<usize>, Conversation)>, String> {
+        let mut leaves: Vec<&String> = self.nodes
+            .iter()
+            .filter(|(_, node)| node.children.iter().all(|c| !self.nodes.contains_key(c)))
+            .map(|(id, _)| id)
+            .collect();
+        leaves.sort();
+
+        let mut paths = Vec::new();
+        for leaf in leaves {
+            let key = format!("{source:?}:{}:{leaf}", self.source_id);
+            let id = Uuid::new_v5(&Uuid::NAMESPACE_OID, key.as_bytes());
+            let (messages, rooted) = self.path_to(leaf, id)?;
+            if !messages.is_empty() {
+                paths.push((leaf, id, messages, rooted));
+            }
+        }
+
+        let current = match self.current.as_ref().filter(|id| self.nodes.contains_key(*id)) {
+            Some(id) => Some(self.follow_to_leaf(id)?),
+            None => None,
+        };
+        let main = paths.iter()
+            .position(|(leaf, _, _, rooted)| Some(*leaf) == current && *rooted)
+            .or_else(|| (0..paths.len()).max_by_key(|&i| {
+                let (_, _, messages, rooted) = &paths[i];
+                (*rooted, messages.len(), messages.last().map(|m| m.timestamp))
+            }));
+        if let Some(main) = main {
+            let path = paths.remove(main);
+            paths.insert(0, path);
+        }
+
+        let mut conversations = Vec::new();
+        let mut branch = 0;
+        for (i, (_, id, messages, _)) in paths.into_iter().enumerate() {
+            let is_main = i == 0;
+            if !is_main {
+                branch += 1;
+            }
+            let name = if is_main { self.title.clone() } else { format!("{} (branch {})", self.title, branch + 1) };
+
+            conversations.push((
+                (!is_main).then_some(branch + 1),
+                Conversation {
+                    id,
+                    name,
+                    messages,
+                    persona_id: None,
+                    knowledge_base_id: None,
+                },
+            ));
+        }
+        Ok(conversations)
+    }
+
+    /// Follows the most recent child from `id` down to a leaf.
+    fn follow_to_leaf<'a>(&'a self, mut id: &'a String) -> Result<&'a String, String> {
+        // A path visiting more nodes than there are runs in circles.
+        for _ in 0..self.nodes.len() {
+            match self.nodes[id].children.iter().rev().find(|c| self.nodes.contains_key(*c)) {
+                Some(next) => id = next,
+                None => return Ok(id),
+            }
+        }
+        Err(CYCLE.to_string())
+    }
+
+    /// The messages from the root down to `leaf`, with ids of their own in `conversation_id`,
+    /// and whether the path reaches a root rather than a parent missing from the export.
+    fn path_to(&self, leaf: &str, conversation_id: Uuid) -> Result<(Vec<Message>, bool), String> {
+        let mut messages = Vec::new();
+        let mut next = Some(leaf);
+        for _ in 0..self.nodes.len() {
+            let Some((id, node)) = next.and_then(|id| self.nodes.get_key_value(id)) else { break };
+            if let Some(message) = &node.message {
+                messages.push(Message { id: Uuid::new_v5(&conversation_id, id.as_bytes()), ..message.clone() });
+            }
+            next = node.parent.as_deref();
+        }
+        if next.is_some_and(|id| self.nodes.contains_key(id)) {
+            return Err(CYCLE.to_string());
+        }
+        let rooted = next.is_none();
+        messages.reverse();
+        for i in 1..messages.len() {
+            messages[i].metadata.parent_id = Some(messages[i - 1].id);
+        }
+        Ok((messages, rooted))
+    }
+}
@@ -0,0 +1,368 @@
+//! Knowledge bases: collections of documents split into chunks and embedded with
+//! `OLLAMA_EMBEDDING_MODEL`. When a prompt names a knowledge base, its chunks closest to
+//! the prompt are added to it as numbered sources the answer cites.
+//!
+//! Documents are either uploaded or read from a directory under `RUSTIC_KNOWLEDGE_DIR`
+//! each time the knowledge base is synced. Vectors are stored on disk next to the
+//! conversations and scanned in full at retrieval, which is quick at the scale of a
+//! personal document collection.
+
+use super::attachments::{extract_text, looks_like_pdf};
+use super::search::{cosine, embed};
+use super::store::Store;
+use crate::model::knowledge::{Citation, KnowledgeBase, KnowledgeDocument, SyncReport};
+use actix_web::error::{ErrorBadRequest, ErrorInternalServerError, ErrorNotFound, ErrorUnsupportedMediaType};
+use actix_web::http::header::{self, HeaderValue};
+use actix_web::{web, Error, HttpResponse};
+use ollama_rs::Ollama;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+const CHUNK_CHARS: usize = 1200;
+const EXCERPT_CHARS: usize = 160;
+
+#[derive(Deserialize)]
+pub struct NewKnowledgeBase {
+    name: String,
+    #[serde(default)]
+    directory: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct UploadQuery {
+    name: String,
+}
+
+/// The chunks of one document, stored under `knowledge_chunks/{document_id}.json`.
+#[derive(Serialize, Deserialize)]
+struct DocumentChunks {
+    /// Vectors of different embedding models can't be compared, so retrieval skips
+    /// documents embedded with another one until they are uploaded or synced again.
+    model: String,
+    chunks: Vec<Chunk>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Chunk {
+    text: String,
+    vector: Vec<f32>,
+}
+
+fn embedding_model() -> Result<String, Error> {
+    env::var("OLLAMA_EMBEDDING_MODEL")
+        .map_err(|_| ErrorBadRequest("Knowledge bases need OLLAMA_EMBEDDING_MODEL to be set"))
+}
+
+/// How many chunks are added to a prompt, `RUSTIC_RAG_TOP_K` (4 by default).
+fn top_k() -> usize {
+    env::var("RUSTIC_RAG_TOP_K").ok().and_then(|k| k.parse().ok()).unwrap_or(4)
+}
+
+/// Splits text into chunks of whole paragraphs up to `CHUNK_CHARS` characters. Longer
+/// paragraphs are cut into pieces of that size.
+fn chunk(text: &str) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    for paragraph in text.split("\n\n").map(str::trim).filter(|p| !p.is_empty()) {
+        let length = paragraph.chars().count();
+        if !current.is_empty() && current.chars().count() + length + 2 > CHUNK_CHARS {
+            chunks.push(std::mem::take(&mut current));
+        }
+        if length > CHUNK_CHARS {
+            let chars: Vec<char> = paragraph.chars().collect();
+            chunks.extend(chars.chunks(CHUNK_CHARS).map(|piece| piece.iter().collect::<String>()));
+            continue;
+        }
+        if !current.is_empty() {
+            current.push_str("\n\n");
+        }
+        current.push_str(paragraph);
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+fn excerpt(text: &str) -> String {
+    match text.char_indices().nth(EXCERPT_CHARS) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+/// Resolves a knowledge base directory, refusing anything outside `RUSTIC_KNOWLEDGE_DIR`.
+fn resolve_directory(directory: &str) -> Result<PathBuf, Error> {
+    let root = env::var("RUSTIC_KNOWLEDGE_DIR")
+        .map_err(|_| ErrorBadRequest("Server-side directories need RUSTIC_KNOWLEDGE_DIR to be set"))?;
+    let root = Path::new(&root).canonicalize().map_err(ErrorInternalServerError)?;
+    let path = root.join(directory)
+        .canonicalize()
+        .map_err(|e| ErrorBadRequest(format!("Cannot open {directory}: {e}")))?;
+    if !path.starts_with(&root) || !path.is_dir() {
+        return Err(ErrorBadRequest(format!("{directory} is not a directory under RUSTIC_KNOWLEDGE_DIR")));
+    }
+    Ok(path)
+}
+
+/// The files under `dir`, skipping hidden ones.
+fn files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut found = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let path = entry.path();
+        match entry.file_type()?.is_dir() {
+            true => found.extend(files(&path)?),
+            false => found.push(path),
+        }
+    }
+    found.sort();
+    Ok(found)
+}
+
+fn load(store: &Store, id: Uuid) -> Result<KnowledgeBase, Error> {
+    store.collection::<KnowledgeBase>("knowledge")
+        .get(id)
+        .map_err(ErrorInternalServerError)?
+        .ok_or_else(|| ErrorNotFound(format!("No knowledge base {id}")))
+}
+
+fn save(store: &Store, knowledge_base: &KnowledgeBase) -> Result<(), Error> {
+    store.collection("knowledge").put(knowledge_base.id, knowledge_base).map_err(ErrorInternalServerError)
+}
+
+fn delete_chunks(store: &Store, documents: &[KnowledgeDocument]) -> Result<(), Error> {
+    let collection = store.collection::<DocumentChunks>("knowledge_chunks");
+    for document in documents {
+        collection.delete(document.id).map_err(ErrorInternalServerError)?;
+    }
+    Ok(())
+}
+
+/// Extracts, chunks and embeds a document, storing its chunks.
+async fn index(
+    store: &Store,
+    ollama: &Ollama,
+    name: String,
+    bytes: Vec<u8>,
+    path: Option<String>,
+) -> Result<KnowledgeDocument, Error> {
+    let model = embedding_model()?;
+    let size = bytes.len();
+    let pdf = looks_like_pdf(&name, &bytes);
+    let text = web::block(move || extract_text(pdf, &bytes))
+        .await
+        .map_err(ErrorInternalServerError)?
+        .map_err(|e| ErrorUnsupportedMediaType(format!("{name}: {e}")))?;
+
+    let mut chunks = Vec::new();
+    for text in chunk(&text) {
+        let vector = embed(ollama, &model, &text).await?;
+        chunks.push(Chunk { text, vector });
+    }
+    if chunks.is_empty() {
+        return Err(ErrorBadRequest(format!("{name} has no text")));
+    }
+
+    let document = KnowledgeDocument { id: Uuid::new_v4(), name, size, chunks: chunks.len(), path };
+    store.collection("knowledge_chunks")
+        .put(document.id, &DocumentChunks { model, chunks })
+        .map_err(ErrorInternalServerError)?;
+    Ok(document)
+}
+
+/// `GET /api/knowledge`
+pub async fn list(store: web::Data<Store>) -> Result<HttpResponse, Error> {
+    let mut knowledge_bases = store.collection::<KnowledgeBase>("knowledge").all().map_err(ErrorInternalServerError)?;
+    knowledge_bases.sort_by_key(|knowledge_base| knowledge_base.name.to_lowercase());
+    Ok(HttpResponse::Ok().json(knowledge_bases))
+}
+
+/// `POST /api/knowledge` with a name and an optional directory under `RUSTIC_KNOWLEDGE_DIR`.
+pub async fn create(body: web::Json<NewKnowledgeBase>, store: web::Data<Store>) -> Result<HttpResponse, Error> {
+    let body = body.into_inner();
+    if body.name.trim().is_empty() {
+        return Err(ErrorBadRequest("A knowledge base needs a name"));
+    }
+
+    let mut knowledge_base = KnowledgeBase::new(body.name.trim().to_string());
+    knowledge_base.directory = body.directory.map(|d| d.trim().to_string()).filter(|d| !d.is_empty());
+    if let Some(directory) = &knowledge_base.directory {
+        resolve_directory(directory)?;
+    }
+
+    save(&store, &knowledge_base)?;
+    Ok(HttpResponse::Created().json(knowledge_base))
+}
+
+/// `GET /api/knowledge/{id}`
+pub async fn get(id: web::Path<Uuid>, store: web::Data<Store>) -> Result<HttpResponse, Error> {
+    Ok(HttpResponse::Ok().json(load(&store, *id)?))
+}
+
+/// `DELETE /api/knowledge/{id}`, with the chunks of all its documents.
+pub async fn delete(id: web::Path<Uuid>, store: web::Data<Store>) -> Result<HttpResponse, Error> {
+    let knowledge_base = load(&store, *id)?;
+    delete_chunks(&store, &knowledge_base.documents)?;
+    store.collection::<KnowledgeBase>("knowledge").delete(*id).map_err(ErrorInternalServerError)?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// `POST /api/knowledge/{id}/documents?name=guide.pdf` with the file as the request body.
+pub async fn upload(
+    id: web::Path<Uuid>,
+    query: web::Query<UploadQuery>,
+    body: web::Bytes,
+    store: web::Data<Store>,
+    ollama: web::Data<Ollama>,
+) -> Result<HttpResponse, Error> {
+    let name = query.name.trim().to_string();
+    if name.is_empty() {
+        return Err(ErrorBadRequest("Missing file name"));
+    }
+    load(&store, *id)?;
+
+    let document = index(&store, &ollama, name, body.to_vec(), None).await?;
+    // Reloaded as other uploads may have finished while this one was embedded.
+    let mut knowledge_base = load(&store, *id)?;
+    knowledge_base.documents.push(document.clone());
+    save(&store, &knowledge_base)?;
+
+    Ok(HttpResponse::Created().json(document))
+}
+
+/// `DELETE /api/knowledge/{id}/documents/{document_id}`
+pub async fn delete_document(path: web::Path<(Uuid, Uuid)>, store: web::Data<Store>) -> Result<HttpResponse, Error> {
+    let (id, document_id) = path.into_inner();
+    let mut knowledge_base = load(&store, id)?;
+    let Some(position) = knowledge_base.documents.iter().position(|d| d.id == document_id) else {
+        return Err(ErrorNotFound(format!("No document {document_id}")));
+    };
+
+    let document = knowledge_base.documents.remove(position);
+    save(&store, &knowledge_base)?;
+    delete_chunks(&store, &[document])?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// `POST /api/knowledge/{id}/sync` re-reads the knowledge base's directory, replacing the
+/// documents read from it before. Files without readable text are reported as skipped.
+pub async fn sync(id: web::Path<Uuid>, store: web::Data<Store>, ollama: web::Data<Ollama>) -> Result<HttpResponse, Error> {
+    let knowledge_base = load(&store, *id)?;
+    let Some(directory) = knowledge_base.directory else {
+        return Err(ErrorBadRequest(format!("{} has no directory to sync", knowledge_base.name)));
+    };
+    let root = resolve_directory(&directory)?;
+    let paths = files(&root).map_err(ErrorInternalServerError)?;
+
+    let mut documents = Vec::new();
+    let mut skipped = Vec::new();
+    for path in paths {
+        let relative = path.strip_prefix(&root).unwrap_or(&path).to_string_lossy().to_string();
+        let bytes = match fs::read(&path) {
+            Ok(bytes) if !bytes.is_empty() => bytes,
+            _ => {
+                skipped.push(relative);
+                continue;
+            }
+        };
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| relative.clone());
+        match index(&store, &ollama, name, bytes, Some(relative.clone())).await {
+            Ok(document) => documents.push(document),
+            Err(e) if e.as_response_error().status_code().is_client_error() => skipped.push(relative),
+            Err(e) => {
+                delete_chunks(&store, &documents)?;
+                return Err(e);
+            }
+        }
+    }
+
+    let mut knowledge_base = load(&store, *id)?;
+    let (previous, uploaded): (Vec<_>, Vec<_>) = knowledge_base.documents.into_iter().partition(|d| d.path.is_some());
+    knowledge_base.documents = uploaded.into_iter().chain(documents).collect();
+    save(&store, &knowledge_base)?;
+    delete_chunks(&store, &previous)?;
+
+    Ok(HttpResponse::Ok().json(SyncReport { knowledge_base, skipped }))
+}
+
+/// `GET /api/knowledge/{id}/documents/{document_id}/chunks/{chunk}` serves the text of a cited chunk.
+pub async fn chunk_text(path: web::Path<(Uuid, Uuid, usize)>, store: web::Data<Store>) -> Result<HttpResponse, Error> {
+    let (id, document_id, chunk) = path.into_inner();
+    let knowledge_base = load(&store, id)?;
+    let not_found = || ErrorNotFound(format!("No chunk {chunk} in document {document_id}"));
+    let document = knowledge_base.documents.iter().find(|d| d.id == document_id).ok_or_else(not_found)?;
+    let text = store.collection::<DocumentChunks>("knowledge_chunks")
+        .get(document.id)
+        .map_err(ErrorInternalServerError)?
+        .and_then(|stored| stored.chunks.into_iter().nth(chunk))
+        .ok_or_else(not_found)?
+        .text;
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/plain; charset=utf-8")
+        .insert_header((header::X_CONTENT_TYPE_OPTIONS, HeaderValue::from_static("nosniff")))
+        .body(format!("{} — chunk {}\n\n{text}", document.name, chunk + 1)))
+}
+
+/// The chunks of a knowledge base closest to `query`, numbered as citations, with their text.
+pub async fn retrieve(store: &Store, ollama: &Ollama, id: Uuid, query: &str) -> Result<Vec<(Citation, String)>, Error> {
+    let knowledge_base = load(store, id)?;
+    if knowledge_base.documents.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let model = embedding_model()?;
+    let query_vector = embed(ollama, &model, query).await?;
+    let collection = store.collection::<DocumentChunks>("knowledge_chunks");
+    let mut scored = Vec::new();
+    for document in &knowledge_base.documents {
+        let Some(stored) = collection.get(document.id).map_err(ErrorInternalServerError)? else { continue };
+        if stored.model != model {
+            continue;
+        }
+        for (i, chunk) in stored.chunks.into_iter().enumerate() {
+            scored.push((cosine(&query_vector, &chunk.vector), document, i, chunk.text));
+        }
+    }
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+    scored.truncate(top_k());
+
+    Ok(scored.into_iter().enumerate().map(|(i, (_, document, chunk, text))| {
+        let citation = Citation {
+            number: i + 1,
+            knowledge_base_id: id,
+            document_id: document.id,
+            document_name: document.name.clone(),
+            chunk,
+            excerpt: excerpt(&text),
+        };
+        (citation, text)
+    }).collect())
+}
+
+/// The prompt as sent to the model for the current turn: the user's text followed by the
+/// retrieved sources. The chat history keeps the text alone.
+pub fn prompt_with_sources(prompt: &str, sources: &[(Citation, String)]) -> String {
+    if sources.is_empty() {
+        return prompt.to_string();
+    }
+
+    let mut prompt = format!(
+        "{prompt}\n\nAnswer using the numbered sources below where they are relevant, and cite the ones you use inline as [1], [2] and so on."
+    );
+    for (citation, text) in sources {
+        prompt.push_str(&format!(
+            "\n\n<source number=\"{}\" document=\"{}\">\n{}\n</source>",
+            citation.number, citation.document_name, text.trim()
+        ));
+    }
+    prompt
+}
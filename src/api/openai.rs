@@ -0,0 +1,302 @@
+//! OpenAI-compatible `/v1/chat/completions` and `/v1/models` endpoints, so IDE plugins
+//! and scripts that speak the OpenAI API can use rustic-ai as their gateway.
+
+use super::{model_name, system_prompt};
+use actix_web::{web, HttpResponse, Error};
+use actix_web::http::StatusCode;
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use ollama_rs::Ollama;
+use ollama_rs::generation::chat::{ChatMessage, ChatMessageFinalResponseData, MessageRole};
+use ollama_rs::generation::chat::request::ChatMessageRequest;
+use ollama_rs::generation::options::GenerationOptions;
+use serde::{Serialize, Deserialize};
+use std::io::Error as StdErr;
+use uuid::Uuid;
+
+#[derive(Deserialize, Debug)]
+pub struct ChatCompletionRequest {
+    #[serde(default)]
+    pub model: String,
+    pub messages: Vec<RequestMessage>,
+    #[serde(default)]
+    pub stream: bool,
+    pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+    pub max_tokens: Option<i32>,
+    pub seed: Option<i32>,
+    pub stop: Option<Stop>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RequestMessage {
+    pub role: String,
+    pub content: Content,
+}
+
+/// Message content is either a plain string or a list of typed parts, of which only
+/// the text parts are forwarded to the model.
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum Content {
+    Text(String),
+    Parts(Vec<ContentPart>),
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ContentPart {
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub text: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum Stop {
+    One(String),
+    Many(Vec<String>),
+}
+
+#[derive(Serialize, Debug)]
+struct ChatCompletion {
+    id: String,
+    object: &'static str,
+    created: i64,
+    model: String,
+    choices: Vec<Choice>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    usage: Option<Usage>,
+}
+
+#[derive(Serialize, Debug)]
+struct Choice {
+    index: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<ResponseMessage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    delta: Option<ResponseMessage>,
+    finish_reason: Option<&'static str>,
+}
+
+#[derive(Serialize, Debug, Default)]
+struct ResponseMessage {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    role: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+struct Usage {
+    prompt_tokens: u32,
+    completion_tokens: u32,
+    total_tokens: u32,
+}
+
+#[derive(Serialize, Debug)]
+struct Model {
+    id: String,
+    object: &'static str,
+    created: i64,
+    owned_by: &'static str,
+}
+
+#[derive(Serialize, Debug)]
+struct ModelList {
+    object: &'static str,
+    data: Vec<Model>,
+}
+
+/// Error body in the shape OpenAI clients expect.
+fn error_response(status: StatusCode, kind: &str, message: impl ToString) -> HttpResponse {
+    HttpResponse::build(status).json(serde_json::json!({
+        "error": {
+            "message": message.to_string(),
+            "type": kind,
+            "code": null,
+        }
+    }))
+}
+
+impl Content {
+    fn into_text(self) -> String {
+        match self {
+            Content::Text(text) => text,
+            Content::Parts(parts) => parts
+                .into_iter()
+                .filter(|part| part.kind == "text")
+                .map(|part| part.text)
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
+}
+
+impl ChatCompletionRequest {
+    fn options(&self) -> GenerationOptions {
+        let mut options = GenerationOptions::default();
+        if let Some(temperature) = self.temperature {
+            options = options.temperature(temperature);
+        }
+        if let Some(top_p) = self.top_p {
+            options = options.top_p(top_p);
+        }
+        if let Some(max_tokens) = self.max_tokens {
+            options = options.num_predict(max_tokens);
+        }
+        if let Some(seed) = self.seed {
+            options = options.seed(seed);
+        }
+        match &self.stop {
+            Some(Stop::One(stop)) => options = options.stop(vec![stop.clone()]),
+            Some(Stop::Many(stop)) => options = options.stop(stop.clone()),
+            None => {}
+        }
+        options
+    }
+
+    /// Converts the request into Ollama messages, injecting the server's system prompt
+    /// when the client did not send one of its own.
+    fn chat_messages(self) -> Result<Vec<ChatMessage>, String> {
+        let mut messages = Vec::with_capacity(self.messages.len() + 1);
+
+        if !self.messages.iter().any(|m| m.role == "system" || m.role == "developer") {
+            messages.push(ChatMessage::system(system_prompt()));
+        }
+
+        for message in self.messages {
+            let role = match message.role.as_str() {
+                "system" | "developer" => MessageRole::System,
+                "user" => MessageRole::User,
+                "assistant" => MessageRole::Assistant,
+                other => return Err(format!("Unsupported message role `{other}`")),
+            };
+            messages.push(ChatMessage::new(role, message.content.into_text()));
+        }
+
+        Ok(messages)
+    }
+}
+
+fn finish_reason(final_data: &ChatMessageFinalResponseData, max_tokens: Option<i32>) -> &'static str {
+    match max_tokens {
+        Some(max) if i32::from(final_data.eval_count) >= max => "length",
+        _ => "stop",
+    }
+}
+
+fn usage(final_data: &ChatMessageFinalResponseData) -> Usage {
+    let prompt_tokens = u32::from(final_data.prompt_eval_count);
+    let completion_tokens = u32::from(final_data.eval_count);
+    Usage {
+        prompt_tokens,
+        completion_tokens,
+        total_tokens: prompt_tokens + completion_tokens,
+    }
+}
+
+fn sse_frame(chunk: &ChatCompletion) -> web::Bytes {
+    let data = serde_json::to_string(chunk).expect("ChatCompletion is serializable");
+    web::Bytes::from(format!("data: {data}\n\n"))
+}
+
+/// `POST /v1/chat/completions`
+pub async fn chat_completions(
+    request: web::Json<ChatCompletionRequest>,
+    ollama: web::Data<Ollama>,
+) -> HttpResponse {
+    let request = request.into_inner();
+    let model = if request.model.is_empty() { model_name() } else { request.model.clone() };
+    let stream = request.stream;
+    let max_tokens = request.max_tokens;
+    let options = request.options();
+
+    let messages = match request.chat_messages() {
+        Ok(messages) => messages,
+        Err(message) => return error_response(StatusCode::BAD_REQUEST, "invalid_request_error", message),
+    };
+
+    let chat_req = ChatMessageRequest::new(model.clone(), messages).options(options);
+    let id = format!("chatcmpl-{}", Uuid::new_v4().simple());
+    let created = Utc::now().timestamp();
+
+    if !stream {
+        let res = match ollama.send_chat_messages(chat_req).await {
+            Ok(res) => res,
+            Err(e) => return error_response(StatusCode::BAD_GATEWAY, "api_error", format!("Ollama error: {e}")),
+        };
+
+        return HttpResponse::Ok().json(ChatCompletion {
+            id,
+            object: "chat.completion",
+            created,
+            model,
+            choices: vec![Choice {
+                index: 0,
+                message: Some(ResponseMessage {
+                    role: Some("assistant"),
+                    content: Some(res.message.map(|m| m.content).unwrap_or_default()),
+                }),
+                delta: None,
+                finish_reason: Some(res.final_data.as_ref().map_or("stop", |d| finish_reason(d, max_tokens))),
+            }],
+            usage: res.final_data.as_ref().map(usage),
+        });
+    }
+
+    let responses = match ollama.send_chat_messages_stream(chat_req).await {
+        Ok(responses) => responses,
+        Err(e) => return error_response(StatusCode::BAD_GATEWAY, "api_error", format!("Ollama error: {e}")),
+    };
+
+    let chunk = move |delta: ResponseMessage, finish_reason: Option<&'static str>, usage: Option<Usage>| ChatCompletion {
+        id: id.clone(),
+        object: "chat.completion.chunk",
+        created,
+        model: model.clone(),
+        choices: vec![Choice { index: 0, message: None, delta: Some(delta), finish_reason }],
+        usage,
+    };
+
+    let first = sse_frame(&chunk(ResponseMessage { role: Some("assistant"), content: None }, None, None));
+
+    let body = futures::stream::once(async move { Ok::<_, Error>(first) })
+        .chain(responses.map(move |res| {
+            let res = res.map_err(|_| Error::from(StdErr::other("Stream error")))?;
+            let content = res.message.map(|m| m.content).filter(|c| !c.is_empty());
+            let frame = chunk(
+                ResponseMessage { role: None, content },
+                res.final_data.as_ref().map(|d| finish_reason(d, max_tokens)),
+                res.final_data.as_ref().map(usage),
+            );
+            Ok(sse_frame(&frame))
+        }))
+        .chain(futures::stream::once(async { Ok(web::Bytes::from_static(b"data: [DONE]\n\n")) }));
+
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        .streaming(body)
+}
+
+/// `GET /v1/models`
+pub async fn models(ollama: web::Data<Ollama>) -> HttpResponse {
+    let models = match ollama.list_local_models().await {
+        Ok(models) => models,
+        Err(e) => return error_response(StatusCode::BAD_GATEWAY, "api_error", format!("Ollama error: {e}")),
+    };
+
+    HttpResponse::Ok().json(ModelList {
+        object: "list",
+        data: models
+            .into_iter()
+            .map(|m| Model {
+                created: DateTime::parse_from_rfc3339(&m.modified_at).map_or(0, |t| t.timestamp()),
+                id: m.name,
+                object: "model",
+                owned_by: "ollama",
+            })
+            .collect(),
+    })
+}
@@ -0,0 +1,131 @@
+//! Personas: named system prompts with few-shot examples, a default model and
+//! generation options, picked when a conversation is started.
+//!
+//! The default persona has the nil id. Until it is edited it is built from
+//! `OLLAMA_SYSTEM_PROMPT`; saving it stores an override, deleting it restores that.
+
+use super::store::Store;
+use super::system_prompt;
+use super::tools::ToolRegistry;
+use crate::model::persona::{ExampleTurn, GenerationSettings, Persona};
+use actix_web::error::{ErrorBadRequest, ErrorInternalServerError, ErrorNotFound};
+use actix_web::{web, Error, HttpResponse};
+use ollama_rs::generation::options::GenerationOptions;
+use std::io;
+use uuid::Uuid;
+
+fn built_in_default() -> Persona {
+    Persona {
+        id: Uuid::nil(),
+        name: "Default".to_string(),
+        system_prompt: system_prompt(),
+        examples: vec![ExampleTurn {
+            user: "Hello there?".to_string(),
+            assistant: "Hello - How may I help you today?".to_string(),
+        }],
+        model: None,
+        options: GenerationSettings::default(),
+        tools: Vec::new(),
+    }
+}
+
+/// The persona with `id`, falling back to the default one when it is unset or was deleted.
+pub fn resolve(store: &Store, id: Option<Uuid>) -> io::Result<Persona> {
+    let personas = store.collection::<Persona>("personas");
+    if let Some(persona) = id.filter(|id| !id.is_nil()).map(|id| personas.get(id)).transpose()?.flatten() {
+        return Ok(persona);
+    }
+    Ok(personas.get(Uuid::nil())?.unwrap_or_else(built_in_default))
+}
+
+pub fn generation_options(settings: &GenerationSettings) -> GenerationOptions {
+    let mut options = GenerationOptions::default();
+    if let Some(temperature) = settings.temperature {
+        options = options.temperature(temperature);
+    }
+    if let Some(top_p) = settings.top_p {
+        options = options.top_p(top_p);
+    }
+    if let Some(top_k) = settings.top_k {
+        options = options.top_k(top_k);
+    }
+    if let Some(num_ctx) = settings.num_ctx {
+        options = options.num_ctx(num_ctx);
+    }
+    if let Some(num_predict) = settings.num_predict {
+        options = options.num_predict(num_predict);
+    }
+    if let Some(seed) = settings.seed {
+        options = options.seed(seed);
+    }
+    options
+}
+
+fn validate(persona: &Persona) -> Result<(), Error> {
+    if persona.name.trim().is_empty() {
+        return Err(ErrorBadRequest("A persona needs a name"));
+    }
+    let tools = ToolRegistry::builtin();
+    if let Some(unknown) = persona.tools.iter().find(|name| !tools.contains(name)) {
+        return Err(ErrorBadRequest(format!("There is no tool named `{unknown}`")));
+    }
+    Ok(())
+}
+
+/// `GET /api/personas`, the default persona first.
+pub async fn list(store: web::Data<Store>) -> Result<HttpResponse, Error> {
+    let mut personas: Vec<Persona> = store.collection::<Persona>("personas")
+        .all()
+        .map_err(ErrorInternalServerError)?
+        .into_iter()
+        .filter(|persona| !persona.id.is_nil())
+        .collect();
+    personas.sort_by_key(|persona| persona.name.to_lowercase());
+    personas.insert(0, resolve(&store, None).map_err(ErrorInternalServerError)?);
+
+    Ok(HttpResponse::Ok().json(personas))
+}
+
+/// `POST /api/personas`
+pub async fn create(persona: web::Json<Persona>, store: web::Data<Store>) -> Result<HttpResponse, Error> {
+    let mut persona = persona.into_inner();
+    validate(&persona)?;
+    persona.id = Uuid::new_v4();
+
+    store.collection("personas").put(persona.id, &persona).map_err(ErrorInternalServerError)?;
+    Ok(HttpResponse::Created().json(persona))
+}
+
+/// `GET /api/personas/{id}`
+pub async fn get(id: web::Path<Uuid>, store: web::Data<Store>) -> Result<HttpResponse, Error> {
+    if id.is_nil() {
+        return Ok(HttpResponse::Ok().json(resolve(&store, None).map_err(ErrorInternalServerError)?));
+    }
+    match store.collection::<Persona>("personas").get(*id).map_err(ErrorInternalServerError)? {
+        Some(persona) => Ok(HttpResponse::Ok().json(persona)),
+        None => Err(ErrorNotFound(format!("No persona {id}"))),
+    }
+}
+
+/// `PUT /api/personas/{id}`, creating the persona when it does not exist yet.
+pub async fn update(
+    id: web::Path<Uuid>,
+    persona: web::Json<Persona>,
+    store: web::Data<Store>,
+) -> Result<HttpResponse, Error> {
+    let mut persona = persona.into_inner();
+    validate(&persona)?;
+    persona.id = *id;
+
+    store.collection("personas").put(persona.id, &persona).map_err(ErrorInternalServerError)?;
+    Ok(HttpResponse::Ok().json(persona))
+}
+
+/// `DELETE /api/personas/{id}`. Conversations started with a deleted persona continue with the default one.
+pub async fn delete(id: web::Path<Uuid>, store: web::Data<Store>) -> Result<HttpResponse, Error> {
+    let deleted = store.collection::<Persona>("personas").delete(*id).map_err(ErrorInternalServerError)?;
+    match deleted || id.is_nil() {
+        true => Ok(HttpResponse::NoContent().finish()),
+        false => Err(ErrorNotFound(format!("No persona {id}"))),
+    }
+}
@@ -0,0 +1,349 @@
+//! Search across the messages of every stored conversation.
+//!
+//! Full-text search ranks messages with BM25 over an in-memory inverted index that is
+//! brought up to date with the conversation files before each query. Semantic search
+//! ranks by cosine similarity of embeddings from `OLLAMA_EMBEDDING_MODEL`, cached on
+//! disk per conversation. Messages without a cached embedding are embedded when a
+//! semantic query first needs them, several requests at a time.
+
+use super::export::escape_html;
+use super::store::Store;
+use crate::model::conversation::{Conversation, Role};
+use crate::model::search::{SearchHit, SearchMode};
+use actix_web::error::{ErrorBadGateway, ErrorBadRequest, ErrorInternalServerError};
+use actix_web::{web, Error, HttpResponse};
+use chrono::{DateTime, Utc};
+use futures::{stream, StreamExt};
+use ollama_rs::Ollama;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::sync::Mutex;
+use std::time::SystemTime;
+use uuid::Uuid;
+
+const SNIPPET_CONTEXT: usize = 80;
+/// Embedding requests in flight at once while catching up on messages without one.
+const EMBED_CONCURRENCY: usize = 8;
+
+#[derive(Deserialize)]
+pub struct SearchQuery {
+    q: String,
+    #[serde(default)]
+    mode: SearchMode,
+    limit: Option<usize>,
+}
+
+struct Document {
+    conversation_id: Uuid,
+    conversation_name: String,
+    role: Role,
+    timestamp: DateTime<Utc>,
+    content: String,
+    length: usize,
+}
+
+#[derive(Default)]
+struct Index {
+    documents: HashMap<Uuid, Document>,
+    /// term -> message id -> term frequency
+    postings: HashMap<String, HashMap<Uuid, u32>>,
+    /// conversation id -> (indexed modification time, message ids)
+    conversations: HashMap<Uuid, (SystemTime, Vec<Uuid>)>,
+    total_length: usize,
+}
+
+/// Embeddings of one conversation's messages, stored under `embeddings/{conversation_id}.json`.
+#[derive(Serialize, Deserialize, Default)]
+struct Embeddings {
+    model: String,
+    vectors: HashMap<Uuid, Vec<f32>>,
+}
+
+#[derive(Default)]
+pub struct SearchIndex(Mutex<Index>);
+
+impl Index {
+    fn remove(&mut self, conversation_id: Uuid) {
+        let Some((_, message_ids)) = self.conversations.remove(&conversation_id) else { return };
+        for id in message_ids {
+            if let Some(document) = self.documents.remove(&id) {
+                self.total_length -= document.length;
+                for term in tokenize(&document.content) {
+                    if let Some(posting) = self.postings.get_mut(&term) {
+                        posting.remove(&id);
+                        if posting.is_empty() {
+                            self.postings.remove(&term);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn insert(&mut self, conversation: Conversation, modified: SystemTime) {
+        let mut message_ids = Vec::with_capacity(conversation.messages.len());
+        for message in conversation.messages {
+            let terms = tokenize(&message.content);
+            for term in &terms {
+                *self.postings.entry(term.clone()).or_default().entry(message.id).or_default() += 1;
+            }
+            self.total_length += terms.len();
+            message_ids.push(message.id);
+            self.documents.insert(message.id, Document {
+                conversation_id: conversation.id,
+                conversation_name: conversation.name.clone(),
+                role: message.role,
+                timestamp: message.timestamp,
+                content: message.content,
+                length: terms.len(),
+            });
+        }
+        self.conversations.insert(conversation.id, (modified, message_ids));
+    }
+
+    /// Reindexes conversations whose files changed since the last query and drops deleted ones.
+    fn refresh(&mut self, store: &Store) -> std::io::Result<()> {
+        let conversations = store.conversations();
+        let modified = conversations.modified()?;
+
+        let live: Vec<Uuid> = modified.iter().filter_map(|(key, _)| Uuid::parse_str(key).ok()).collect();
+        let deleted: Vec<Uuid> = self.conversations.keys().filter(|id| !live.contains(id)).copied().collect();
+        for id in deleted {
+            self.remove(id);
+        }
+
+        for (key, time) in modified {
+            let Ok(id) = Uuid::parse_str(&key) else { continue };
+            if self.conversations.get(&id).is_some_and(|(indexed, _)| *indexed == time) {
+                continue;
+            }
+            self.remove(id);
+            if let Some(conversation) = conversations.get(id)? {
+                self.insert(conversation, time);
+            }
+        }
+        Ok(())
+    }
+
+    fn full_text(&self, query: &str, limit: usize) -> Vec<SearchHit> {
+        const K1: f32 = 1.2;
+        const B: f32 = 0.75;
+
+        let mut terms = tokenize(query);
+        terms.sort();
+        terms.dedup();
+
+        let count = self.documents.len() as f32;
+        let average_length = (self.total_length as f32 / count.max(1.0)).max(1.0);
+        let mut scores: HashMap<Uuid, f32> = HashMap::new();
+
+        for term in &terms {
+            let Some(posting) = self.postings.get(term) else { continue };
+            let frequency = posting.len() as f32;
+            let idf = ((count - frequency + 0.5) / (frequency + 0.5) + 1.0).ln();
+            for (id, tf) in posting {
+                let tf = *tf as f32;
+                let length = self.documents[id].length as f32;
+                *scores.entry(*id).or_default() += idf * tf * (K1 + 1.0) / (tf + K1 * (1.0 - B + B * length / average_length));
+            }
+        }
+
+        let mut ranked: Vec<(Uuid, f32)> = scores.into_iter().collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+        ranked.truncate(limit);
+
+        ranked.into_iter()
+            .map(|(id, score)| self.hit(id, score, highlight(&self.documents[&id].content, &terms)))
+            .collect()
+    }
+
+    fn hit(&self, id: Uuid, score: f32, snippet: String) -> SearchHit {
+        let document = &self.documents[&id];
+        SearchHit {
+            conversation_id: document.conversation_id,
+            conversation_name: document.conversation_name.clone(),
+            message_id: id,
+            role: document.role,
+            timestamp: document.timestamp,
+            snippet,
+            score,
+        }
+    }
+}
+
+impl SearchIndex {
+    async fn semantic(&self, store: &Store, ollama: &Ollama, query: &str, limit: usize) -> Result<Vec<SearchHit>, Error> {
+        let model = env::var("OLLAMA_EMBEDDING_MODEL")
+            .map_err(|_| ErrorBadRequest("Semantic search needs OLLAMA_EMBEDDING_MODEL to be set"))?;
+        let collection = store.collection::<Embeddings>("embeddings");
+
+        let query_vector = embed(ollama, &model, query).await?;
+
+        // Snapshot what needs scoring so the index is not locked across backend calls.
+        let documents: Vec<(Uuid, Uuid, String)> = {
+            let index = self.0.lock().unwrap();
+            index.documents.iter().map(|(id, d)| (*id, d.conversation_id, d.content.clone())).collect()
+        };
+
+        let mut by_conversation: HashMap<Uuid, Vec<(Uuid, String)>> = HashMap::new();
+        for (id, conversation_id, content) in documents {
+            by_conversation.entry(conversation_id).or_default().push((id, content));
+        }
+
+        let mut embeddings: HashMap<Uuid, Embeddings> = HashMap::new();
+        for conversation_id in by_conversation.keys() {
+            let cached = collection
+                .get(*conversation_id)
+                .map_err(ErrorInternalServerError)?
+                .filter(|e| e.model == model)
+                .unwrap_or_else(|| Embeddings { model: model.clone(), vectors: HashMap::new() });
+            embeddings.insert(*conversation_id, cached);
+        }
+
+        let missing: Vec<(Uuid, Uuid, &str)> = by_conversation.iter()
+            .flat_map(|(conversation_id, messages)| messages.iter().map(move |(id, content)| (*conversation_id, *id, content.as_str())))
+            .filter(|(conversation_id, id, _)| !embeddings[conversation_id].vectors.contains_key(id))
+            .collect();
+        let model = &model;
+        let embedded: Vec<_> = stream::iter(missing)
+            .map(|(conversation_id, id, content)| async move { (conversation_id, id, embed(ollama, model, content).await) })
+            .buffer_unordered(EMBED_CONCURRENCY)
+            .collect()
+            .await;
+
+        // Vectors embedded before a failure are still saved, so the next search picks up from there.
+        let mut failure = None;
+        let mut changed = HashSet::new();
+        for (conversation_id, id, vector) in embedded {
+            match vector {
+                Ok(vector) => {
+                    embeddings.get_mut(&conversation_id).unwrap().vectors.insert(id, vector);
+                    changed.insert(conversation_id);
+                }
+                Err(e) => failure = failure.or(Some(e)),
+            }
+        }
+        for conversation_id in changed {
+            let messages = &by_conversation[&conversation_id];
+            let embeddings = embeddings.get_mut(&conversation_id).unwrap();
+            embeddings.vectors.retain(|id, _| messages.iter().any(|(m, _)| m == id));
+            collection.put(conversation_id, embeddings).map_err(ErrorInternalServerError)?;
+        }
+        if let Some(e) = failure {
+            return Err(e);
+        }
+
+        let mut scored = Vec::new();
+        for (conversation_id, messages) in &by_conversation {
+            let vectors = &embeddings[conversation_id].vectors;
+            for (id, _) in messages {
+                scored.push((*id, cosine(&query_vector, &vectors[id])));
+            }
+        }
+
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+        scored.truncate(limit);
+
+        let index = self.0.lock().unwrap();
+        Ok(scored.into_iter()
+            .filter(|(id, _)| index.documents.contains_key(id))
+            .map(|(id, score)| index.hit(id, score, highlight(&index.documents[&id].content, &[])))
+            .collect())
+    }
+}
+
+/// `GET /api/search?q=...&mode=fulltext|semantic&limit=20`
+pub async fn search(
+    query: web::Query<SearchQuery>,
+    index: web::Data<SearchIndex>,
+    store: web::Data<Store>,
+    ollama: web::Data<Ollama>,
+) -> Result<HttpResponse, Error> {
+    let limit = query.limit.unwrap_or(20).min(100);
+    if query.q.trim().is_empty() {
+        return Ok(HttpResponse::Ok().json(Vec::<SearchHit>::new()));
+    }
+
+    index.0.lock().unwrap().refresh(&store).map_err(ErrorInternalServerError)?;
+
+    let hits = match query.mode {
+        SearchMode::FullText => index.0.lock().unwrap().full_text(&query.q, limit),
+        SearchMode::Semantic => index.semantic(&store, &ollama, &query.q, limit).await?,
+    };
+
+    Ok(HttpResponse::Ok().json(hits))
+}
+
+pub(super) async fn embed(ollama: &Ollama, model: &str, text: &str) -> Result<Vec<f32>, Error> {
+    let response = ollama
+        .generate_embeddings(model.to_string(), text.to_string(), None)
+        .await
+        .map_err(|e| ErrorBadGateway(format!("Ollama error: {e}")))?;
+    Ok(response.embeddings.into_iter().map(|x| x as f32).collect())
+}
+
+pub(super) fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = a.iter().map(|x| x * x).sum::<f32>().sqrt() * b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm == 0.0 { 0.0 } else { dot / norm }
+}
+
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Cuts a window of `content` around the first matching term and marks every match in it.
+/// Without terms (or matches) the start of the message is used.
+fn highlight(content: &str, terms: &[String]) -> String {
+    let lower = content.to_lowercase();
+    // Lowercasing can change byte lengths; fall back to no highlighting when it does.
+    let terms: &[String] = if lower.len() == content.len() { terms } else { &[] };
+
+    let mut matches: Vec<(usize, usize)> = Vec::new();
+    for term in terms {
+        let mut from = 0;
+        while let Some(pos) = lower[from..].find(term.as_str()) {
+            let start = from + pos;
+            let end = start + term.len();
+            let is_word_start = !lower[..start].chars().next_back().is_some_and(char::is_alphanumeric);
+            if is_word_start {
+                matches.push((start, end));
+            }
+            from = end;
+        }
+    }
+    matches.sort();
+
+    let first = matches.first().map_or(0, |(start, _)| *start);
+    let mut start = first.saturating_sub(SNIPPET_CONTEXT);
+    while !content.is_char_boundary(start) {
+        start -= 1;
+    }
+    let mut end = (first + SNIPPET_CONTEXT * 2).min(content.len());
+    while !content.is_char_boundary(end) {
+        end += 1;
+    }
+
+    let mut snippet = String::new();
+    if start > 0 {
+        snippet.push('…');
+    }
+    let mut cursor = start;
+    for (match_start, match_end) in matches {
+        if match_start < cursor || match_end > end {
+            continue;
+        }
+        snippet.push_str(&escape_html(&content[cursor..match_start]));
+        snippet.push_str(&format!("<mark>{}</mark>", escape_html(&content[match_start..match_end])));
+        cursor = match_end;
+    }
+    snippet.push_str(&escape_html(&content[cursor..end]));
+    if end < content.len() {
+        snippet.push('…');
+    }
+    snippet
+}
@@ -0,0 +1,267 @@
+//! Read-only share links: immutable snapshots of a conversation served at `/s/:token`.
+//!
+//! There are no user accounts yet, so a link belongs to the browser that created it, as
+//! told by its `X-Client-Id` header (see `templates`): only that browser lists and
+//! revokes it. Links created before owners were recorded can only be removed from
+//! `RUSTIC_DATA_DIR/shares`.
+
+use super::store::Store;
+use super::templates::require_client_id;
+use crate::model::conversation::Conversation;
+use crate::model::share::{ShareRequest, ShareSummary, SharedConversation, ShareView};
+use actix_web::error::{ErrorInternalServerError, ErrorNotFound};
+use actix_web::{web, Error, HttpRequest, HttpResponse};
+use chrono::Utc;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// PBKDF2 rounds for new passwords, as OWASP recommends for HMAC-SHA-256.
+const PBKDF2_ITERATIONS: u32 = 600_000;
+/// Wrong passwords a link accepts before further attempts have to wait.
+const FREE_ATTEMPTS: u32 = 5;
+/// The longest wait between attempts, reached after repeated failures.
+const MAX_LOCKOUT: Duration = Duration::from_secs(15 * 60);
+
+#[derive(Serialize, Deserialize)]
+struct Share {
+    token: String,
+    conversation_id: Uuid,
+    conversation: Conversation,
+    created_at: i64,
+    expires_at: Option<i64>,
+    password: Option<PasswordHash>,
+    /// The client id of the browser that created the link.
+    #[serde(default)]
+    owner: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum PasswordHash {
+    Pbkdf2 { salt: String, iterations: u32, pbkdf2_sha256: String },
+    /// Links created before passwords were hashed with PBKDF2; upgraded when next unlocked.
+    Sha256 { salt: String, sha256: String },
+}
+
+impl PasswordHash {
+    fn new(password: &str) -> PasswordHash {
+        let salt = random_string(16);
+        let pbkdf2_sha256 = hex(&pbkdf2_sha256(password.as_bytes(), salt.as_bytes(), PBKDF2_ITERATIONS));
+        PasswordHash::Pbkdf2 { salt, iterations: PBKDF2_ITERATIONS, pbkdf2_sha256 }
+    }
+
+    fn matches(&self, password: &str) -> bool {
+        let (expected, actual) = match self {
+            PasswordHash::Pbkdf2 { salt, iterations, pbkdf2_sha256: expected } => {
+                (expected, hex(&pbkdf2_sha256(password.as_bytes(), salt.as_bytes(), *iterations)))
+            }
+            PasswordHash::Sha256 { salt, sha256: expected } => {
+                (expected, format!("{:x}", Sha256::digest(format!("{salt}:{password}"))))
+            }
+        };
+        constant_time_eq(expected.as_bytes(), actual.as_bytes())
+    }
+
+    fn is_outdated(&self) -> bool {
+        !matches!(self, PasswordHash::Pbkdf2 { iterations, .. } if *iterations >= PBKDF2_ITERATIONS)
+    }
+}
+
+/// PBKDF2 (RFC 8018) with HMAC-SHA-256, deriving a single 32-byte block.
+fn pbkdf2_sha256(password: &[u8], salt: &[u8], iterations: u32) -> [u8; 32] {
+    // HMAC (RFC 2104) with the keyed inner and outer states computed once.
+    let key: Vec<u8> = match password.len() > 64 {
+        true => Sha256::digest(password).to_vec(),
+        false => password.to_vec(),
+    };
+    let pad = |byte: u8| {
+        let mut block = [byte; 64];
+        block.iter_mut().zip(&key).for_each(|(b, k)| *b ^= k);
+        Sha256::new().chain_update(block)
+    };
+    let (inner, outer) = (pad(0x36), pad(0x5c));
+    let hmac = |message: &[u8]| -> [u8; 32] {
+        let inner = inner.clone().chain_update(message).finalize();
+        outer.clone().chain_update(inner).finalize().into()
+    };
+
+    let mut block = hmac(&[salt, &1u32.to_be_bytes()].concat());
+    let mut derived = block;
+    for _ in 1..iterations {
+        block = hmac(&block);
+        derived.iter_mut().zip(&block).for_each(|(d, b)| *d ^= b);
+    }
+    derived
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Compares without returning early, so the time taken doesn't tell how much matched.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |difference, (x, y)| difference | (x ^ y)) == 0
+}
+
+/// Failed unlocks of a link, kept in memory.
+struct Attempts {
+    failures: u32,
+    locked_until: Instant,
+}
+
+fn attempts() -> &'static Mutex<HashMap<String, Attempts>> {
+    static ATTEMPTS: OnceLock<Mutex<HashMap<String, Attempts>>> = OnceLock::new();
+    ATTEMPTS.get_or_init(Default::default)
+}
+
+/// How long until the link may be tried again, if it is locked.
+fn locked_for(token: &str) -> Option<Duration> {
+    let attempts = attempts().lock().unwrap();
+    let remaining = attempts.get(token)?.locked_until.checked_duration_since(Instant::now())?;
+    Some(remaining).filter(|remaining| !remaining.is_zero())
+}
+
+/// Records a wrong password. Past `FREE_ATTEMPTS`, each failure doubles the wait.
+fn record_failure(token: &str) {
+    let mut attempts = attempts().lock().unwrap();
+    let entry = attempts.entry(token.to_string()).or_insert(Attempts { failures: 0, locked_until: Instant::now() });
+    entry.failures += 1;
+    if let Some(over) = entry.failures.checked_sub(FREE_ATTEMPTS) {
+        let wait = Duration::from_secs(1u64 << over.min(10)).min(MAX_LOCKOUT);
+        entry.locked_until = Instant::now() + wait;
+    }
+}
+
+impl Share {
+    fn is_expired(&self) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= Utc::now().timestamp())
+    }
+
+    fn is_owned_by(&self, client: &str) -> bool {
+        self.owner.as_deref() == Some(client)
+    }
+
+    fn summary(&self) -> ShareSummary {
+        ShareSummary {
+            token: self.token.clone(),
+            created_at: self.created_at,
+            expires_at: self.expires_at,
+            password_protected: self.password.is_some(),
+        }
+    }
+}
+
+fn random_string(len: usize) -> String {
+    rand::thread_rng().sample_iter(&Alphanumeric).take(len).map(char::from).collect()
+}
+
+/// Tokens double as file names, so anything but the alphanumerics we generate is rejected.
+fn is_valid_token(token: &str) -> bool {
+    !token.is_empty() && token.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+/// A share whether or not it expired.
+fn load_any(store: &Store, token: &str) -> io::Result<Option<Share>> {
+    if !is_valid_token(token) {
+        return Ok(None);
+    }
+    store.collection::<Share>("shares").get_key(token)
+}
+
+fn load(store: &Store, token: &str) -> io::Result<Option<Share>> {
+    Ok(load_any(store, token)?.filter(|share| !share.is_expired()))
+}
+
+/// Resolves a share for the public page. `None` when it does not exist, expired or was revoked.
+pub fn open(store: &Store, token: &str, password: Option<&str>) -> io::Result<Option<ShareView>> {
+    let Some(mut share) = load(store, token)? else { return Ok(None) };
+
+    if let Some(hash) = &share.password {
+        let Some(password) = password else {
+            return Ok(Some(ShareView::PasswordRequired { incorrect: false }));
+        };
+        if let Some(wait) = locked_for(token) {
+            return Ok(Some(ShareView::TooManyAttempts { retry_after_secs: wait.as_secs().max(1) }));
+        }
+        if !hash.matches(password) {
+            record_failure(token);
+            return Ok(Some(ShareView::PasswordRequired { incorrect: true }));
+        }
+        attempts().lock().unwrap().remove(token);
+        if hash.is_outdated() {
+            share.password = Some(PasswordHash::new(password));
+            store.collection("shares").put_key(token, &share)?;
+        }
+    }
+
+    Ok(Some(ShareView::Available(SharedConversation {
+        name: share.conversation.name,
+        messages: share.conversation.messages,
+        created_at: share.created_at,
+        expires_at: share.expires_at,
+    })))
+}
+
+/// `POST /api/conversations/{id}/shares`
+pub async fn create(
+    req: HttpRequest,
+    id: web::Path<Uuid>,
+    request: web::Json<ShareRequest>,
+    store: web::Data<Store>,
+) -> Result<HttpResponse, Error> {
+    let owner = require_client_id(&req)?;
+    let conversation = store.conversations()
+        .get(*id)
+        .map_err(ErrorInternalServerError)?
+        .ok_or_else(|| ErrorNotFound(format!("No conversation {id}")))?;
+
+    let created_at = Utc::now().timestamp();
+    let share = Share {
+        token: random_string(22),
+        conversation_id: conversation.id,
+        conversation,
+        created_at,
+        expires_at: request.expires_in_hours.map(|hours| created_at + i64::from(hours) * 3600),
+        password: request.password.as_deref().filter(|p| !p.is_empty()).map(PasswordHash::new),
+        owner: Some(owner.to_string()),
+    };
+
+    store.collection("shares").put_key(&share.token, &share).map_err(ErrorInternalServerError)?;
+    Ok(HttpResponse::Created().json(share.summary()))
+}
+
+/// `GET /api/conversations/{id}/shares`, the requesting browser's links.
+pub async fn list(req: HttpRequest, id: web::Path<Uuid>, store: web::Data<Store>) -> Result<HttpResponse, Error> {
+    let owner = require_client_id(&req)?;
+    let mut shares: Vec<ShareSummary> = store.collection::<Share>("shares")
+        .all()
+        .map_err(ErrorInternalServerError)?
+        .into_iter()
+        .filter(|share| share.conversation_id == *id && share.is_owned_by(owner) && !share.is_expired())
+        .map(|share| share.summary())
+        .collect();
+    shares.sort_by_key(|share| share.created_at);
+
+    Ok(HttpResponse::Ok().json(shares))
+}
+
+/// `DELETE /api/shares/{token}`, by the browser that created the link.
+pub async fn revoke(req: HttpRequest, token: web::Path<String>, store: web::Data<Store>) -> Result<HttpResponse, Error> {
+    let owner = require_client_id(&req)?;
+    let shares = store.collection::<Share>("shares");
+    // Someone else's link is reported as missing, so tokens can't be probed this way.
+    match load_any(&store, &token).map_err(ErrorInternalServerError)? {
+        Some(share) if share.is_owned_by(owner) => {
+            shares.delete_key(&token).map_err(ErrorInternalServerError)?;
+            Ok(HttpResponse::NoContent().finish())
+        }
+        _ => Err(ErrorNotFound("No such share")),
+    }
+}
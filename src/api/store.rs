@@ -0,0 +1,153 @@
+//! File-backed persistence. Every record is a pretty-printed JSON file under
+//! `RUSTIC_DATA_DIR` (defaults to `./data`), grouped by collection. Uploaded files
+//! are kept as raw blobs under `blobs/`.
+
+use crate::model::conversation::Conversation;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::env;
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::marker::PhantomData;
+use std::path::PathBuf;
+use std::time::SystemTime;
+use uuid::Uuid;
+
+pub struct Store {
+    root: PathBuf,
+}
+
+/// A directory of raw files keyed by id.
+pub struct Blobs {
+    dir: PathBuf,
+}
+
+/// A directory of JSON records of one type, keyed by id.
+pub struct Collection<T> {
+    dir: PathBuf,
+    record: PhantomData<T>,
+}
+
+impl Store {
+    pub fn new(root: impl Into<PathBuf>) -> Store {
+        Store { root: root.into() }
+    }
+
+    pub fn from_env() -> Store {
+        Store::new(env::var("RUSTIC_DATA_DIR").unwrap_or_else(|_| "data".to_string()))
+    }
+
+    pub fn collection<T: Serialize + DeserializeOwned>(&self, name: &str) -> Collection<T> {
+        Collection { dir: self.root.join(name), record: PhantomData }
+    }
+
+    pub fn conversations(&self) -> Collection<Conversation> {
+        self.collection("conversations")
+    }
+
+    pub fn blobs(&self, name: &str) -> Blobs {
+        Blobs { dir: self.root.join("blobs").join(name) }
+    }
+}
+
+impl Blobs {
+    pub fn get(&self, id: Uuid) -> io::Result<Option<Vec<u8>>> {
+        match fs::read(self.dir.join(id.to_string())) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn put(&self, id: Uuid, bytes: &[u8]) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let tmp = self.dir.join(format!(".{id}.tmp"));
+        fs::write(&tmp, bytes)?;
+        fs::rename(tmp, self.dir.join(id.to_string()))
+    }
+}
+
+impl<T: Serialize + DeserializeOwned> Collection<T> {
+    fn path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{id}.json"))
+    }
+
+    pub fn get(&self, id: Uuid) -> io::Result<Option<T>> {
+        self.get_key(&id.to_string())
+    }
+
+    /// Like [`Collection::get`] for records keyed by something other than a UUID.
+    /// Keys must already be safe file names.
+    pub fn get_key(&self, key: &str) -> io::Result<Option<T>> {
+        match fs::read(self.path(key)) {
+            Ok(bytes) => serde_json::from_slice(&bytes).map(Some).map_err(io::Error::from),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn put(&self, id: Uuid, record: &T) -> io::Result<()> {
+        self.put_key(&id.to_string(), record)
+    }
+
+    pub fn put_key(&self, key: &str, record: &T) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        // Write then rename so readers never see a half-written record.
+        let tmp = self.dir.join(format!(".{key}.json.tmp"));
+        fs::write(&tmp, serde_json::to_vec_pretty(record)?)?;
+        fs::rename(tmp, self.path(key))
+    }
+
+    pub fn delete(&self, id: Uuid) -> io::Result<bool> {
+        self.delete_key(&id.to_string())
+    }
+
+    pub fn delete_key(&self, key: &str) -> io::Result<bool> {
+        match fs::remove_file(self.path(key)) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// The key and last modification time of every record, for callers keeping derived data in sync.
+    pub fn modified(&self) -> io::Result<Vec<(String, SystemTime)>> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        let mut records = Vec::new();
+        for entry in entries {
+            let entry = entry?;
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                if let Some(key) = path.file_stem().and_then(|stem| stem.to_str()) {
+                    records.push((key.to_string(), entry.metadata()?.modified()?));
+                }
+            }
+        }
+        Ok(records)
+    }
+
+    /// Every readable record of the collection, skipping files that fail to parse.
+    pub fn all(&self) -> io::Result<Vec<T>> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        let mut records = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                if let Ok(record) = serde_json::from_slice(&fs::read(&path)?) {
+                    records.push(record);
+                }
+            }
+        }
+        Ok(records)
+    }
+}
@@ -0,0 +1,135 @@
+//! Prompt template library.
+//!
+//! There are no user accounts yet: each browser sends a random id it keeps in local
+//! storage as `X-Client-Id`, and the client that creates a template owns it. Private
+//! templates are only listed for their owner, shared ones for everybody, and only the
+//! owner may change or delete either. This keeps libraries apart; it is not access control.
+
+use super::store::Store;
+use crate::model::template::{PromptTemplate, CLIENT_ID_HEADER};
+use actix_web::error::{ErrorBadRequest, ErrorForbidden, ErrorInternalServerError, ErrorNotFound};
+use actix_web::{web, Error, HttpRequest, HttpResponse};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize)]
+struct Template {
+    id: Uuid,
+    name: String,
+    body: String,
+    shared: bool,
+    owner: String,
+}
+
+impl Template {
+    fn view(self, client: Option<&str>) -> PromptTemplate {
+        PromptTemplate {
+            editable: client == Some(self.owner.as_str()),
+            id: self.id,
+            name: self.name,
+            body: self.body,
+            shared: self.shared,
+        }
+    }
+}
+
+pub(super) fn client_id(req: &HttpRequest) -> Option<&str> {
+    req.headers()
+        .get(CLIENT_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| !id.is_empty() && id.len() <= 64)
+}
+
+pub(super) fn require_client_id(req: &HttpRequest) -> Result<&str, Error> {
+    client_id(req).ok_or_else(|| ErrorBadRequest(format!("Missing {CLIENT_ID_HEADER} header")))
+}
+
+fn validate(template: &PromptTemplate) -> Result<(), Error> {
+    if template.name.trim().is_empty() {
+        return Err(ErrorBadRequest("A template needs a name"));
+    }
+    if template.body.trim().is_empty() {
+        return Err(ErrorBadRequest("A template needs a body"));
+    }
+    Ok(())
+}
+
+/// `GET /api/templates`: the caller's own templates and every shared one, by name.
+pub async fn list(req: HttpRequest, store: web::Data<Store>) -> Result<HttpResponse, Error> {
+    let client = client_id(&req);
+    let mut templates: Vec<PromptTemplate> = store.collection::<Template>("templates")
+        .all()
+        .map_err(ErrorInternalServerError)?
+        .into_iter()
+        .filter(|template| template.shared || client == Some(template.owner.as_str()))
+        .map(|template| template.view(client))
+        .collect();
+    templates.sort_by_key(|template| template.name.to_lowercase());
+
+    Ok(HttpResponse::Ok().json(templates))
+}
+
+/// `POST /api/templates`
+pub async fn create(
+    req: HttpRequest,
+    template: web::Json<PromptTemplate>,
+    store: web::Data<Store>,
+) -> Result<HttpResponse, Error> {
+    let owner = require_client_id(&req)?;
+    let template = template.into_inner();
+    validate(&template)?;
+
+    let template = Template {
+        id: Uuid::new_v4(),
+        name: template.name,
+        body: template.body,
+        shared: template.shared,
+        owner: owner.to_string(),
+    };
+    store.collection("templates").put(template.id, &template).map_err(ErrorInternalServerError)?;
+    Ok(HttpResponse::Created().json(template.view(Some(owner))))
+}
+
+/// `PUT /api/templates/{id}`, creating the template when it does not exist yet.
+pub async fn update(
+    req: HttpRequest,
+    id: web::Path<Uuid>,
+    template: web::Json<PromptTemplate>,
+    store: web::Data<Store>,
+) -> Result<HttpResponse, Error> {
+    let owner = require_client_id(&req)?;
+    let template = template.into_inner();
+    validate(&template)?;
+
+    let templates = store.collection::<Template>("templates");
+    if let Some(existing) = templates.get(*id).map_err(ErrorInternalServerError)? {
+        if existing.owner != owner {
+            return Err(ErrorForbidden("Only the template's owner can change it"));
+        }
+    }
+
+    let template = Template {
+        id: *id,
+        name: template.name,
+        body: template.body,
+        shared: template.shared,
+        owner: owner.to_string(),
+    };
+    templates.put(template.id, &template).map_err(ErrorInternalServerError)?;
+    Ok(HttpResponse::Ok().json(template.view(Some(owner))))
+}
+
+/// `DELETE /api/templates/{id}`
+pub async fn delete(req: HttpRequest, id: web::Path<Uuid>, store: web::Data<Store>) -> Result<HttpResponse, Error> {
+    let owner = require_client_id(&req)?;
+    let templates = store.collection::<Template>("templates");
+
+    match templates.get(*id).map_err(ErrorInternalServerError)? {
+        None => Err(ErrorNotFound(format!("No template {id}"))),
+        Some(existing) if existing.owner != owner => Err(ErrorForbidden("Only the template's owner can delete it")),
+        Some(_) => {
+            templates.delete(*id).map_err(ErrorInternalServerError)?;
+            Ok(HttpResponse::NoContent().finish())
+        }
+    }
+}
@@ -0,0 +1,194 @@
+//! Tools the model can call while answering.
+//!
+//! ollama-rs has no support for Ollama's native tool calls, so tools are offered
+//! through the prompt: the enabled tools and their JSON schemas are described after
+//! the system prompt, and the model calls one by replying with a
+//! `<tool_call>{"name": ..., "arguments": {...}}</tool_call>` block. The server runs
+//! the tool, sends the result back as a `<tool_result>` message and lets the model
+//! continue, up to `MAX_ROUNDS` calls per prompt. This also works with models that
+//! were not trained for tool use.
+//!
+//! Each persona enables a subset of the tools of [`ToolRegistry::builtin`]. The `sql`
+//! tool is added on its own once a CSV or TSV file is attached to the conversation.
+
+use super::store::Store;
+use crate::model::attachment::Attachment;
+use crate::model::tool::{ToolInfo, ToolStep, ToolTable};
+use actix_web::{Error, HttpResponse};
+use futures::future::BoxFuture;
+use serde::Deserialize;
+use serde_json::Value;
+use std::io;
+
+mod calculator;
+mod datetime;
+mod sql;
+mod units;
+
+/// How many tool calls the model may make before it has to answer.
+pub const MAX_ROUNDS: usize = 5;
+
+const CALL_START: &str = "<tool_call>";
+const CALL_END: &str = "</tool_call>";
+
+pub trait Tool: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn description(&self) -> &'static str;
+    /// JSON schema of the `arguments` object the tool is called with.
+    fn parameters(&self) -> Value;
+    /// Runs the tool. Errors are reported to the model, which can retry.
+    fn execute(&self, arguments: Value) -> BoxFuture<'_, Result<ToolOutput, String>>;
+}
+
+/// What a tool returns: the text given to the model and, for tabular results, the
+/// rows to show the user.
+pub struct ToolOutput {
+    pub text: String,
+    pub table: Option<ToolTable>,
+}
+
+impl From<String> for ToolOutput {
+    fn from(text: String) -> Self {
+        ToolOutput { text, table: None }
+    }
+}
+
+pub struct ToolRegistry {
+    tools: Vec<Box<dyn Tool>>,
+}
+
+impl ToolRegistry {
+    /// Every tool the server provides.
+    pub fn builtin() -> ToolRegistry {
+        ToolRegistry {
+            tools: vec![Box::new(calculator::Calculator), Box::new(datetime::DateTime), Box::new(units::UnitConverter)],
+        }
+    }
+
+    /// The tools named in a persona, ignoring names that don't exist (anymore).
+    pub fn with(names: &[String]) -> ToolRegistry {
+        let mut registry = ToolRegistry::builtin();
+        registry.tools.retain(|tool| names.iter().any(|name| name == tool.name()));
+        registry
+    }
+
+    /// Adds the `sql` tool over the conversation's CSV and TSV attachments.
+    pub fn add_tables(&mut self, store: &Store, tables: &[Attachment]) -> io::Result<()> {
+        if !tables.is_empty() {
+            self.tools.push(Box::new(sql::Sql::load(store, tables)?));
+        }
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tools.is_empty()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.tools.iter().any(|tool| tool.name() == name)
+    }
+
+    pub fn infos(&self) -> Vec<ToolInfo> {
+        self.tools.iter()
+            .map(|tool| ToolInfo { name: tool.name().to_string(), description: tool.description().to_string() })
+            .collect()
+    }
+
+    /// Instructions appended to the system prompt describing the tools and how to call them.
+    pub fn instructions(&self) -> String {
+        let mut instructions = format!(
+            "You can use tools. To call one, reply with a single tool call block and nothing after it, for example:\n\
+             {CALL_START}{{\"name\": \"calculator\", \"arguments\": {{\"expression\": \"2 * (3 + 4)\"}}}}{CALL_END}\n\
+             The result comes back in a <tool_result> message. Only call a tool when it helps; otherwise answer directly. \
+             Never make up tool results.\n\nAvailable tools:"
+        );
+        for tool in &self.tools {
+            instructions.push_str(&format!("\n- {}: {} Arguments: {}", tool.name(), tool.description(), tool.parameters()));
+        }
+        instructions
+    }
+
+    /// Runs a call, reporting unknown tools and failures as error steps.
+    pub async fn run(&self, call: ToolCall) -> ToolStep {
+        let result = match self.tools.iter().find(|tool| tool.name() == call.name) {
+            Some(tool) => tool.execute(call.arguments.clone()).await,
+            None => Err(format!("There is no tool named `{}`", call.name)),
+        };
+        let (result, table, is_error) = match result {
+            Ok(output) => (output.text, output.table, false),
+            Err(e) => (e, None, true),
+        };
+        ToolStep { name: call.name, arguments: call.arguments, result, is_error, table }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ToolCall {
+    pub name: String,
+    #[serde(default)]
+    pub arguments: Value,
+}
+
+/// The tool call a reply ends with, if any. A block that is not valid JSON is
+/// returned as a call of the empty name, so the model hears about the mistake.
+pub fn parse_call(reply: &str) -> Option<ToolCall> {
+    let start = reply.find(CALL_START)? + CALL_START.len();
+    let body = &reply[start..];
+    let body = body.find(CALL_END).map_or(body, |end| &body[..end]).trim();
+    let body = body.trim_start_matches("```json").trim_start_matches("```").trim_end_matches("```").trim();
+
+    Some(serde_json::from_str(body).unwrap_or_else(|e| ToolCall {
+        name: String::new(),
+        arguments: Value::String(format!("Invalid tool call: {e}")),
+    }))
+}
+
+/// The message giving a tool's result back to the model.
+pub fn result_message(step: &ToolStep) -> String {
+    let status = if step.is_error { " error=\"true\"" } else { "" };
+    format!("<tool_result name=\"{}\"{status}>\n{}\n</tool_result>", step.name, step.result)
+}
+
+/// Holds back streamed text that may be the start of a tool call, so the user sees
+/// the model's answer without the call blocks.
+#[derive(Default)]
+pub struct CallFilter {
+    pending: String,
+    in_call: bool,
+}
+
+impl CallFilter {
+    /// Takes the next token, returning the text that can be shown.
+    pub fn push(&mut self, token: &str) -> String {
+        if self.in_call {
+            return String::new();
+        }
+        self.pending.push_str(token);
+        if let Some(start) = self.pending.find(CALL_START) {
+            self.in_call = true;
+            let visible = self.pending[..start].to_string();
+            self.pending.clear();
+            return visible;
+        }
+
+        // Keep back the longest tail that could still grow into `<tool_call>`.
+        let keep = (1..CALL_START.len())
+            .rev()
+            .find(|&n| self.pending.ends_with(&CALL_START[..n]))
+            .unwrap_or(0);
+        let visible = self.pending[..self.pending.len() - keep].to_string();
+        self.pending.drain(..self.pending.len() - keep);
+        visible
+    }
+
+    /// The text still held back once the reply is complete.
+    pub fn finish(&mut self) -> String {
+        self.in_call = false;
+        std::mem::take(&mut self.pending)
+    }
+}
+
+/// `GET /api/tools`
+pub async fn list() -> Result<HttpResponse, Error> {
+    Ok(HttpResponse::Ok().json(ToolRegistry::builtin().infos()))
+}
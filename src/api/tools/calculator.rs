@@ -0,0 +1,196 @@
+use super::{Tool, ToolOutput};
+use futures::future::{self, BoxFuture, FutureExt};
+use serde_json::{json, Value};
+
+/// How deeply parentheses, function calls, signs and powers may nest. The expression comes
+/// from model output, and each level is a recursive call on the server's stack.
+const MAX_DEPTH: usize = 64;
+
+/// Evaluates arithmetic, which models get wrong surprisingly often.
+pub struct Calculator;
+
+impl Tool for Calculator {
+    fn name(&self) -> &'static str {
+        "calculator"
+    }
+
+    fn description(&self) -> &'static str {
+        "Evaluates an arithmetic expression with + - * / % ^, parentheses, the constants pi and e, \
+         and the functions sqrt, abs, ln, log10, exp, sin, cos, tan, round, floor and ceil."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": { "expression": { "type": "string", "description": "e.g. (1.5 + 2) * sqrt(16)" } },
+            "required": ["expression"]
+        })
+    }
+
+    fn execute(&self, arguments: Value) -> BoxFuture<'_, Result<ToolOutput, String>> {
+        let result = match arguments["expression"].as_str() {
+            Some(expression) => evaluate(expression).map(format_number),
+            None => Err("Missing `expression`".to_string()),
+        };
+        future::ready(result.map(ToolOutput::from)).boxed()
+    }
+}
+
+fn format_number(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{value:.0}")
+    } else {
+        format!("{value}")
+    }
+}
+
+fn evaluate(expression: &str) -> Result<f64, String> {
+    let mut parser = Parser { chars: expression.chars().filter(|c| !c.is_whitespace()).collect(), position: 0, depth: 0 };
+    let value = parser.sum()?;
+    match parser.peek() {
+        None if value.is_finite() => Ok(value),
+        None => Err("The result is not a finite number".to_string()),
+        Some(c) => Err(format!("Unexpected `{c}`")),
+    }
+}
+
+/// Recursive descent over `sum := product (('+'|'-') product)*`,
+/// `product := power (('*'|'/'|'%') power)*`, `power := unary ('^' power)?`.
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let matches = self.peek() == Some(c);
+        if matches {
+            self.position += 1;
+        }
+        matches
+    }
+
+    /// Runs `parse` one nesting level deeper.
+    fn nested(&mut self, parse: fn(&mut Parser) -> Result<f64, String>) -> Result<f64, String> {
+        if self.depth >= MAX_DEPTH {
+            return Err("The expression is nested too deeply".to_string());
+        }
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn sum(&mut self) -> Result<f64, String> {
+        let mut value = self.product()?;
+        loop {
+            if self.eat('+') {
+                value += self.product()?;
+            } else if self.eat('-') {
+                value -= self.product()?;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn product(&mut self) -> Result<f64, String> {
+        let mut value = self.power()?;
+        loop {
+            if self.eat('*') {
+                value *= self.power()?;
+            } else if self.eat('/') {
+                value /= self.power()?;
+            } else if self.eat('%') {
+                value %= self.power()?;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn power(&mut self) -> Result<f64, String> {
+        let base = self.unary()?;
+        match self.eat('^') {
+            true => Ok(base.powf(self.nested(Parser::power)?)),
+            false => Ok(base),
+        }
+    }
+
+    fn unary(&mut self) -> Result<f64, String> {
+        if self.eat('-') {
+            return Ok(-self.nested(Parser::unary)?);
+        }
+        if self.eat('+') {
+            return self.nested(Parser::unary);
+        }
+        self.atom()
+    }
+
+    fn atom(&mut self) -> Result<f64, String> {
+        if self.eat('(') {
+            let value = self.nested(Parser::sum)?;
+            return match self.eat(')') {
+                true => Ok(value),
+                false => Err("Missing `)`".to_string()),
+            };
+        }
+
+        let start = self.position;
+        match self.peek() {
+            Some(c) if c.is_ascii_digit() || c == '.' => {
+                while self.peek().is_some_and(|c| c.is_ascii_digit() || c == '.') {
+                    self.position += 1;
+                }
+                // Scientific notation such as 1.5e3.
+                if self.peek() == Some('e') && self.chars.get(self.position + 1).is_some_and(|c| c.is_ascii_digit() || *c == '-') {
+                    self.position += 2;
+                    while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                        self.position += 1;
+                    }
+                }
+                let number: String = self.chars[start..self.position].iter().collect();
+                number.parse().map_err(|_| format!("Invalid number `{number}`"))
+            }
+            Some(c) if c.is_ascii_alphabetic() => {
+                while self.peek().is_some_and(|c| c.is_ascii_alphanumeric()) {
+                    self.position += 1;
+                }
+                let name: String = self.chars[start..self.position].iter().collect();
+                match name.as_str() {
+                    "pi" => return Ok(std::f64::consts::PI),
+                    "e" => return Ok(std::f64::consts::E),
+                    _ => {}
+                }
+                if !self.eat('(') {
+                    return Err(format!("Unknown constant `{name}`"));
+                }
+                let argument = self.nested(Parser::sum)?;
+                if !self.eat(')') {
+                    return Err("Missing `)`".to_string());
+                }
+                match name.as_str() {
+                    "sqrt" => Ok(argument.sqrt()),
+                    "abs" => Ok(argument.abs()),
+                    "ln" => Ok(argument.ln()),
+                    "log10" | "log" => Ok(argument.log10()),
+                    "exp" => Ok(argument.exp()),
+                    "sin" => Ok(argument.sin()),
+                    "cos" => Ok(argument.cos()),
+                    "tan" => Ok(argument.tan()),
+                    "round" => Ok(argument.round()),
+                    "floor" => Ok(argument.floor()),
+                    "ceil" => Ok(argument.ceil()),
+                    _ => Err(format!("Unknown function `{name}`")),
+                }
+            }
+            Some(c) => Err(format!("Unexpected `{c}`")),
+            None => Err("Unexpected end of expression".to_string()),
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use super::{Tool, ToolOutput};
+use chrono::{FixedOffset, Local, Utc};
+use futures::future::{self, BoxFuture, FutureExt};
+use serde_json::{json, Value};
+
+/// Tells the model the current date and time, which it can't know otherwise.
+pub struct DateTime;
+
+impl Tool for DateTime {
+    fn name(&self) -> &'static str {
+        "datetime"
+    }
+
+    fn description(&self) -> &'static str {
+        "Returns the current date, time and weekday, in the server's time zone unless a UTC offset is given."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": { "utc_offset": { "type": "string", "description": "e.g. +02:00, -05:30 or Z" } }
+        })
+    }
+
+    fn execute(&self, arguments: Value) -> BoxFuture<'_, Result<ToolOutput, String>> {
+        let now = match arguments["utc_offset"].as_str().map(str::trim) {
+            None | Some("") => Ok(Local::now().fixed_offset()),
+            Some(offset) => parse_offset(offset).map(|offset| Utc::now().with_timezone(&offset)),
+        };
+        future::ready(now.map(|now| now.format("%A, %Y-%m-%d %H:%M:%S (UTC%:z)").to_string().into())).boxed()
+    }
+}
+
+fn parse_offset(offset: &str) -> Result<FixedOffset, String> {
+    let invalid = || format!("Invalid UTC offset `{offset}`, expected e.g. +02:00");
+    if offset.eq_ignore_ascii_case("z") || offset.eq_ignore_ascii_case("utc") {
+        return Ok(FixedOffset::east_opt(0).expect("zero offset is valid"));
+    }
+
+    let (sign, rest) = match offset.split_at_checked(1) {
+        Some(("+", rest)) => (1, rest),
+        Some(("-", rest)) => (-1, rest),
+        _ => return Err(invalid()),
+    };
+    let (hours, minutes) = rest.split_once(':').unwrap_or((rest, "0"));
+    let hours: i32 = hours.parse().map_err(|_| invalid())?;
+    let minutes: i32 = minutes.parse().map_err(|_| invalid())?;
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60)).ok_or_else(invalid)
+}
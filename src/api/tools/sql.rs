@@ -0,0 +1,266 @@
+use super::{Tool, ToolOutput};
+use crate::api::store::Store;
+use crate::model::attachment::{Attachment, TSV};
+use crate::model::tool::ToolTable;
+use futures::future::{BoxFuture, FutureExt};
+use rusqlite::hooks::{AuthAction, AuthContext, Authorization};
+use rusqlite::limits::Limit;
+use rusqlite::types::{Value as SqlValue, ValueRef};
+use rusqlite::Connection;
+use serde_json::{json, Value};
+use std::io;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Rows a query returns at most; the rest are left out.
+const MAX_ROWS: usize = 100;
+/// How long a query may run before it is interrupted.
+const TIMEOUT: Duration = Duration::from_secs(5);
+/// Characters of a cell the model sees.
+const MAX_CELL_CHARS: usize = 200;
+/// Pragmas that take an argument only to describe the schema.
+const SCHEMA_PRAGMAS: &[&str] = &["table_info", "table_xinfo", "table_list", "index_list", "index_info", "index_xinfo", "foreign_key_list"];
+
+/// Runs read-only SQLite queries over the CSV and TSV files attached to a conversation.
+/// Every call loads the files into a fresh in-memory database that is switched to
+/// query-only mode and can't attach other databases, so a query can read nothing else
+/// and change nothing that outlives it.
+pub struct Sql {
+    tables: Arc<Vec<Table>>,
+}
+
+struct Table {
+    name: String,
+    file: String,
+    columns: Vec<(String, ColumnType)>,
+    rows: Vec<Vec<String>>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ColumnType {
+    Integer,
+    Real,
+    Text,
+}
+
+impl Sql {
+    /// Reads and parses the attached files. Rows that can't be parsed are skipped.
+    pub fn load(store: &Store, attachments: &[Attachment]) -> io::Result<Sql> {
+        let blobs = store.blobs("attachments");
+        let mut tables: Vec<Table> = Vec::new();
+        for attachment in attachments {
+            let bytes = blobs.get(attachment.id)?
+                .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("No attachment {}", attachment.id)))?;
+            let delimiter = if attachment.content_type.starts_with(TSV) { b'\t' } else { b',' };
+            let mut reader = csv::ReaderBuilder::new().delimiter(delimiter).flexible(true).from_reader(bytes.as_slice());
+
+            let headers: Vec<String> = reader.byte_headers()
+                .map(|headers| headers.iter().map(|h| String::from_utf8_lossy(h).trim().to_string()).collect())
+                .unwrap_or_default();
+            if headers.is_empty() {
+                continue;
+            }
+            let rows: Vec<Vec<String>> = reader.byte_records()
+                .filter_map(Result::ok)
+                .map(|record| (0..headers.len())
+                    .map(|i| record.get(i).map(|cell| String::from_utf8_lossy(cell).trim().to_string()).unwrap_or_default())
+                    .collect())
+                .collect();
+
+            let taken: Vec<&str> = tables.iter().map(|t| t.name.as_str()).collect();
+            let name = unique(identifier(&attachment.name), &taken);
+            let mut columns: Vec<(String, ColumnType)> = Vec::new();
+            for (i, header) in headers.iter().enumerate() {
+                let header = if header.is_empty() { format!("column{}", i + 1) } else { header.clone() };
+                let taken: Vec<&str> = columns.iter().map(|(name, _)| name.as_str()).collect();
+                columns.push((unique(header, &taken), column_type(rows.iter().map(|row| row[i].as_str()))));
+            }
+            tables.push(Table { name, file: attachment.name.clone(), columns, rows });
+        }
+        Ok(Sql { tables: Arc::new(tables) })
+    }
+}
+
+impl Tool for Sql {
+    fn name(&self) -> &'static str {
+        "sql"
+    }
+
+    fn description(&self) -> &'static str {
+        "Runs a read-only SQLite query (a single SELECT) over the CSV and TSV files attached to the conversation \
+         and returns up to 100 rows. Empty cells are NULL."
+    }
+
+    fn parameters(&self) -> Value {
+        let tables: Vec<String> = self.tables.iter().map(|table| {
+            let columns: Vec<String> = table.columns.iter()
+                .map(|(name, kind)| format!("{} {}", quote(name), kind.sql()))
+                .collect();
+            format!("{} from {}, {} rows: ({})", quote(&table.name), table.file, table.rows.len(), columns.join(", "))
+        }).collect();
+        json!({
+            "type": "object",
+            "properties": {
+                "query": { "type": "string", "description": format!("SQLite query over the tables {}", tables.join("; ")) }
+            },
+            "required": ["query"]
+        })
+    }
+
+    fn execute(&self, arguments: Value) -> BoxFuture<'_, Result<ToolOutput, String>> {
+        let tables = self.tables.clone();
+        async move {
+            let query = arguments["query"].as_str().ok_or("Missing `query`")?.to_string();
+            tokio::task::spawn_blocking(move || run(&tables, &query))
+                .await
+                .map_err(|e| format!("The query failed: {e}"))?
+        }.boxed()
+    }
+}
+
+fn run(tables: &[Table], query: &str) -> Result<ToolOutput, String> {
+    let connection = open(tables).map_err(|e| format!("Could not load the tables: {e}"))?;
+    let started = Instant::now();
+    connection.progress_handler(1000, Some(move || started.elapsed() > TIMEOUT));
+    let error = |e: rusqlite::Error| match started.elapsed() > TIMEOUT {
+        true => format!("The query took longer than {} seconds and was stopped", TIMEOUT.as_secs()),
+        false => e.to_string(),
+    };
+
+    let mut statement = connection.prepare(query).map_err(error)?;
+    if !statement.readonly() {
+        return Err("Only read-only queries are allowed".to_string());
+    }
+    let columns: Vec<String> = statement.column_names().into_iter().map(String::from).collect();
+    let mut table = ToolTable { columns, rows: Vec::new(), truncated: false };
+    let mut rows = statement.query([]).map_err(error)?;
+    while let Some(row) = rows.next().map_err(error)? {
+        if table.rows.len() == MAX_ROWS {
+            table.truncated = true;
+            break;
+        }
+        let cells = (0..table.columns.len()).map(|i| row.get_ref(i).map(cell)).collect::<Result<_, _>>();
+        table.rows.push(cells.map_err(error)?);
+    }
+
+    Ok(ToolOutput { text: describe(&table), table: Some(table) })
+}
+
+fn open(tables: &[Table]) -> rusqlite::Result<Connection> {
+    let mut connection = Connection::open_in_memory()?;
+    connection.set_limit(Limit::SQLITE_LIMIT_ATTACHED, 0);
+
+    let transaction = connection.transaction()?;
+    for table in tables {
+        let columns: Vec<String> = table.columns.iter().map(|(name, kind)| format!("{} {}", quote(name), kind.sql())).collect();
+        transaction.execute(&format!("CREATE TABLE {} ({})", quote(&table.name), columns.join(", ")), [])?;
+        let placeholders = vec!["?"; table.columns.len()].join(", ");
+        let mut insert = transaction.prepare(&format!("INSERT INTO {} VALUES ({placeholders})", quote(&table.name)))?;
+        for row in &table.rows {
+            insert.execute(rusqlite::params_from_iter(row.iter().zip(&table.columns).map(|(cell, (_, kind))| value(cell, *kind))))?;
+        }
+    }
+    transaction.commit()?;
+
+    connection.pragma_update(None, "query_only", true)?;
+    // `query_only` and the statement's `readonly` check don't cover pragmas: turning
+    // `query_only` off again counts as read-only.
+    connection.authorizer(Some(authorize));
+    Ok(connection)
+}
+
+fn authorize(context: AuthContext) -> Authorization {
+    match context.action {
+        AuthAction::Pragma { pragma_name, pragma_value: Some(_) } if !SCHEMA_PRAGMAS.contains(&pragma_name) => Authorization::Deny,
+        AuthAction::Attach { .. } | AuthAction::Detach { .. } => Authorization::Deny,
+        _ => Authorization::Allow,
+    }
+}
+
+impl ColumnType {
+    fn sql(self) -> &'static str {
+        match self {
+            ColumnType::Integer => "INTEGER",
+            ColumnType::Real => "REAL",
+            ColumnType::Text => "TEXT",
+        }
+    }
+}
+
+/// The narrowest type all of a column's non-empty cells fit.
+fn column_type<'a>(cells: impl Iterator<Item = &'a str> + Clone) -> ColumnType {
+    let mut cells = cells.filter(|cell| !cell.is_empty());
+    if cells.clone().all(|cell| cell.parse::<i64>().is_ok()) {
+        ColumnType::Integer
+    } else if cells.all(|cell| cell.parse::<f64>().is_ok()) {
+        ColumnType::Real
+    } else {
+        ColumnType::Text
+    }
+}
+
+fn value(cell: &str, kind: ColumnType) -> SqlValue {
+    if cell.is_empty() {
+        return SqlValue::Null;
+    }
+    match kind {
+        ColumnType::Integer => cell.parse().map_or_else(|_| SqlValue::Text(cell.to_string()), SqlValue::Integer),
+        ColumnType::Real => cell.parse().map_or_else(|_| SqlValue::Text(cell.to_string()), SqlValue::Real),
+        ColumnType::Text => SqlValue::Text(cell.to_string()),
+    }
+}
+
+fn cell(value: ValueRef) -> Option<String> {
+    match value {
+        ValueRef::Null => None,
+        ValueRef::Integer(n) => Some(n.to_string()),
+        ValueRef::Real(n) => Some(n.to_string()),
+        ValueRef::Text(text) => Some(String::from_utf8_lossy(text).into_owned()),
+        ValueRef::Blob(blob) => Some(format!("<{} bytes>", blob.len())),
+    }
+}
+
+/// The result as the model sees it: one line per row, cells separated by ` | `.
+fn describe(table: &ToolTable) -> String {
+    let mut lines = vec![table.columns.join(" | ")];
+    lines.extend(table.rows.iter().map(|row| {
+        row.iter()
+            .map(|cell| match cell {
+                Some(cell) if cell.chars().count() > MAX_CELL_CHARS => format!("{}…", cell.chars().take(MAX_CELL_CHARS).collect::<String>()),
+                Some(cell) => cell.clone(),
+                None => "NULL".to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }));
+    match (table.rows.is_empty(), table.truncated) {
+        (true, _) => lines.push("(no rows)".to_string()),
+        (false, true) => lines.push(format!("(only the first {MAX_ROWS} rows are shown)")),
+        (false, false) => {}
+    }
+    lines.join("\n")
+}
+
+/// A table name from a file name, e.g. `Sales 2024.csv` becomes `sales_2024`.
+fn identifier(file: &str) -> String {
+    let stem = file.rsplit_once('.').map_or(file, |(stem, _)| stem);
+    let name: String = stem.to_lowercase().chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect();
+    let name = name.trim_matches('_');
+    match name.chars().next() {
+        None => "data".to_string(),
+        Some(c) if c.is_ascii_digit() => format!("t_{name}"),
+        Some(_) => name.to_string(),
+    }
+}
+
+/// `name`, or `name_2`, `name_3`... if it is taken.
+fn unique(name: String, taken: &[&str]) -> String {
+    if !taken.contains(&name.as_str()) {
+        return name;
+    }
+    (2..).map(|n| format!("{name}_{n}")).find(|candidate| !taken.contains(&candidate.as_str())).expect("a free name")
+}
+
+fn quote(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
@@ -0,0 +1,151 @@
+use super::{Tool, ToolOutput};
+use futures::future::{self, BoxFuture, FutureExt};
+use serde_json::{json, Value};
+
+/// Converts between units of length, mass, volume, time, speed, data and temperature.
+pub struct UnitConverter;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Dimension {
+    Length,
+    Mass,
+    Volume,
+    Time,
+    Speed,
+    Data,
+}
+
+/// Unit names and their size in the dimension's base unit (m, kg, l, s, m/s, B).
+/// Temperatures are handled separately as their scales have different zeros.
+const UNITS: &[(&[&str], Dimension, f64)] = &[
+    (&["mm", "millimeter", "millimetre"], Dimension::Length, 0.001),
+    (&["cm", "centimeter", "centimetre"], Dimension::Length, 0.01),
+    (&["m", "meter", "metre"], Dimension::Length, 1.0),
+    (&["km", "kilometer", "kilometre"], Dimension::Length, 1000.0),
+    (&["in", "inch"], Dimension::Length, 0.0254),
+    (&["ft", "foot", "feet"], Dimension::Length, 0.3048),
+    (&["yd", "yard"], Dimension::Length, 0.9144),
+    (&["mi", "mile"], Dimension::Length, 1609.344),
+    (&["nmi", "nautical mile"], Dimension::Length, 1852.0),
+    (&["mg", "milligram"], Dimension::Mass, 1e-6),
+    (&["g", "gram"], Dimension::Mass, 0.001),
+    (&["kg", "kilogram"], Dimension::Mass, 1.0),
+    (&["t", "tonne", "metric ton"], Dimension::Mass, 1000.0),
+    (&["oz", "ounce"], Dimension::Mass, 0.028349523125),
+    (&["lb", "lbs", "pound"], Dimension::Mass, 0.45359237),
+    (&["st", "stone"], Dimension::Mass, 6.35029318),
+    (&["ml", "milliliter", "millilitre"], Dimension::Volume, 0.001),
+    (&["l", "liter", "litre"], Dimension::Volume, 1.0),
+    (&["m3", "cubic meter", "cubic metre"], Dimension::Volume, 1000.0),
+    (&["tsp", "teaspoon"], Dimension::Volume, 0.00492892159375),
+    (&["tbsp", "tablespoon"], Dimension::Volume, 0.01478676478125),
+    (&["cup"], Dimension::Volume, 0.2365882365),
+    (&["floz", "fl oz", "fluid ounce"], Dimension::Volume, 0.0295735295625),
+    (&["pt", "pint"], Dimension::Volume, 0.473176473),
+    (&["qt", "quart"], Dimension::Volume, 0.946352946),
+    (&["gal", "gallon"], Dimension::Volume, 3.785411784),
+    (&["ms", "millisecond"], Dimension::Time, 0.001),
+    (&["s", "sec", "second"], Dimension::Time, 1.0),
+    (&["min", "minute"], Dimension::Time, 60.0),
+    (&["h", "hr", "hour"], Dimension::Time, 3600.0),
+    (&["d", "day"], Dimension::Time, 86400.0),
+    (&["wk", "week"], Dimension::Time, 604800.0),
+    (&["yr", "year"], Dimension::Time, 31557600.0),
+    (&["m/s", "mps"], Dimension::Speed, 1.0),
+    (&["km/h", "kph", "kmh"], Dimension::Speed, 1.0 / 3.6),
+    (&["mph"], Dimension::Speed, 0.44704),
+    (&["kn", "knot"], Dimension::Speed, 1852.0 / 3600.0),
+    (&["b", "byte"], Dimension::Data, 1.0),
+    (&["kb", "kilobyte"], Dimension::Data, 1e3),
+    (&["mb", "megabyte"], Dimension::Data, 1e6),
+    (&["gb", "gigabyte"], Dimension::Data, 1e9),
+    (&["tb", "terabyte"], Dimension::Data, 1e12),
+    (&["kib", "kibibyte"], Dimension::Data, 1024.0),
+    (&["mib", "mebibyte"], Dimension::Data, 1048576.0),
+    (&["gib", "gibibyte"], Dimension::Data, 1073741824.0),
+    (&["tib", "tebibyte"], Dimension::Data, 1099511627776.0),
+];
+
+const TEMPERATURES: &[&[&str]] = &[&["c", "celsius", "°c"], &["f", "fahrenheit", "°f"], &["k", "kelvin"]];
+
+impl Tool for UnitConverter {
+    fn name(&self) -> &'static str {
+        "convert_units"
+    }
+
+    fn description(&self) -> &'static str {
+        "Converts a value between units of length, mass, volume, time, speed, data size or temperature \
+         (e.g. km to mi, lb to kg, cup to ml, GiB to GB, F to C)."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "value": { "type": "number" },
+                "from": { "type": "string", "description": "unit to convert from, e.g. km" },
+                "to": { "type": "string", "description": "unit to convert to, e.g. mi" }
+            },
+            "required": ["value", "from", "to"]
+        })
+    }
+
+    fn execute(&self, arguments: Value) -> BoxFuture<'_, Result<ToolOutput, String>> {
+        let result = (|| {
+            let value = arguments["value"].as_f64().ok_or("Missing numeric `value`")?;
+            let from = arguments["from"].as_str().ok_or("Missing `from` unit")?;
+            let to = arguments["to"].as_str().ok_or("Missing `to` unit")?;
+            let converted = convert(value, from, to)?;
+            Ok(format!("{value} {from} = {} {to}", round(converted)))
+        })();
+        future::ready(result.map(ToolOutput::from)).boxed()
+    }
+}
+
+/// Finds a unit by name, ignoring case and a plural `s`.
+fn unit(name: &str) -> Option<(Dimension, f64)> {
+    let name = name.trim().to_lowercase();
+    let singular = name.strip_suffix('s').unwrap_or(&name);
+    UNITS.iter()
+        .find(|(names, _, _)| names.contains(&name.as_str()))
+        .or_else(|| UNITS.iter().find(|(names, _, _)| singular.len() > 1 && names.contains(&singular)))
+        .map(|(_, dimension, factor)| (*dimension, *factor))
+}
+
+fn temperature(name: &str) -> Option<usize> {
+    let name = name.trim().to_lowercase();
+    let name = name.strip_prefix("degrees ").unwrap_or(&name);
+    TEMPERATURES.iter().position(|names| names.contains(&name))
+}
+
+fn convert(value: f64, from: &str, to: &str) -> Result<f64, String> {
+    if let (Some(from), Some(to)) = (temperature(from), temperature(to)) {
+        let kelvin = match from {
+            0 => value + 273.15,
+            1 => (value - 32.0) * 5.0 / 9.0 + 273.15,
+            _ => value,
+        };
+        return Ok(match to {
+            0 => kelvin - 273.15,
+            1 => (kelvin - 273.15) * 9.0 / 5.0 + 32.0,
+            _ => kelvin,
+        });
+    }
+
+    let (from_dimension, from_factor) = unit(from).ok_or_else(|| format!("Unknown unit `{from}`"))?;
+    let (to_dimension, to_factor) = unit(to).ok_or_else(|| format!("Unknown unit `{to}`"))?;
+    if from_dimension != to_dimension {
+        let name = |dimension: Dimension| format!("{dimension:?}").to_lowercase();
+        return Err(format!("Cannot convert {} to {}", name(from_dimension), name(to_dimension)));
+    }
+    Ok(value * from_factor / to_factor)
+}
+
+/// Rounds to 10 significant digits so conversions don't show float noise.
+fn round(value: f64) -> f64 {
+    if value == 0.0 {
+        return 0.0;
+    }
+    let scale = 10f64.powi(9 - value.abs().log10().floor() as i32);
+    (value * scale).round() / scale
+}
@@ -0,0 +1,61 @@
+//! Whether a model can look at images.
+//!
+//! Models listed in `RUSTIC_VISION_MODELS` (comma separated, matched without the
+//! `:tag`) always count as vision models. Any other model is looked up with Ollama's
+//! `/api/show`: newer servers list a `vision` capability, older ones report the
+//! `clip` or `mllama` projector family for llava and llama3.2-vision style models.
+
+use ollama_rs::Ollama;
+use serde::Deserialize;
+use std::env;
+
+#[derive(Deserialize, Default)]
+struct ModelShow {
+    #[serde(default)]
+    capabilities: Vec<String>,
+    #[serde(default)]
+    details: ModelDetails,
+}
+
+#[derive(Deserialize, Default)]
+struct ModelDetails {
+    #[serde(default)]
+    families: Option<Vec<String>>,
+}
+
+fn base_name(model: &str) -> &str {
+    model.split(':').next().unwrap_or(model)
+}
+
+fn listed(model: &str) -> bool {
+    env::var("RUSTIC_VISION_MODELS")
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .any(|listed| !listed.is_empty() && base_name(listed) == base_name(model))
+}
+
+/// Asks Ollama whether `model` accepts images. Unknown models and failed lookups
+/// count as text-only.
+pub async fn supports_images(ollama: &Ollama, model: &str) -> bool {
+    if listed(model) {
+        return true;
+    }
+
+    let show = async {
+        let mut response = awc::Client::new()
+            .post(format!("{}/api/show", ollama.uri()))
+            .send_json(&serde_json::json!({ "model": model, "name": model }))
+            .await
+            .ok()?;
+        if !response.status().is_success() {
+            return None;
+        }
+        // The reply carries the whole modelfile and template, well over awc's default limit.
+        response.json::<ModelShow>().limit(16 * 1024 * 1024).await.ok()
+    };
+
+    let Some(show) = show.await else { return false };
+    show.capabilities.iter().any(|c| c == "vision")
+        || show.details.families.unwrap_or_default().iter().any(|f| f == "clip" || f == "mllama")
+}
@@ -1,19 +1,35 @@
-use crate::model::conversation::{Conversation, Message};
+use crate::model::conversation::{Conversation, Message, Role};
+use crate::model::event::{ChatEvent, ChatRequest, ClientMessage, CommandRequest, SessionCommand};
 use leptos::*;
 use leptos_meta::*;
 use leptos_router::*;
 use std::cell::RefCell;
-use futures_util::sink::SinkExt;
-use futures::StreamExt;
 use std::rc::Rc;
-use chrono::Utc;
 use uuid::Uuid;
-use gloo_net::websocket::futures::WebSocket;
-use gloo_net::websocket::Message as WsMessage;
 
+mod clipboard;
+pub mod commands;
 mod components;
+mod settings;
+mod share;
+mod transport;
+use commands::CommandAction;
 use components::chat_area::ChatArea;
-use components::prompt_area::PromptArea;
+use components::export_menu::ExportMenu;
+use components::knowledge_picker::KnowledgePicker;
+use components::persona_picker::PersonaPicker;
+use components::prompt_area::{Prompt, PromptArea};
+use components::search_box::SearchBox;
+use components::share_menu::ShareMenu;
+use settings::SettingsPage;
+use share::SharePage;
+use transport::Transport;
+
+/// Messages loaded at a time when opening a stored conversation and scrolling up through it.
+const PAGE_SIZE: usize = 50;
+
+/// Local storage key remembering whether generation stats are shown under answers.
+const SHOW_STATS_KEY: &str = "rustic-ai.show-stats";
 
 #[component]
 pub fn App() -> impl IntoView {
@@ -33,6 +49,9 @@ pub fn App() -> impl IntoView {
             <main class="max-w-4xl mx-auto my-2 px-2 text-gray-700 dark:text-gray-100">
                 <Routes>
                     <Route path="" view=ChatPage/>
+                    <Route path="/c/:id" view=ChatPage/>
+                    <Route path="/s/:token" view=SharePage/>
+                    <Route path="/settings" view=SettingsPage/>
                     <Route path="/*any" view=NotFound/>
                 </Routes>
             </main>
@@ -45,62 +64,117 @@ pub fn App() -> impl IntoView {
 fn ChatPage() -> impl IntoView {
     let (conversation, set_conversation) = create_signal(Conversation::new("Default Conversation".to_string()));
     let (is_loading, set_is_loading) = create_signal(false);
+    let (persona_id, set_persona_id) = create_signal(None::<Uuid>);
+    let (knowledge_base_id, set_knowledge_base_id) = create_signal(None::<Uuid>);
+    let notice = create_rw_signal(None::<String>);
+    // Whether the conversation has messages before the loaded ones.
+    let (has_older, set_has_older) = create_signal(false);
+    let jump_to = create_rw_signal(None::<Uuid>);
+    let show_stats = create_rw_signal(true);
+
+    // The choice is kept per browser, so it is read once mounted and stored whenever it changes.
+    create_effect(move |_| {
+        if let Some(show) = local_storage().and_then(|storage| storage.get_item(SHOW_STATS_KEY).ok().flatten()) {
+            show_stats.set(show != "false");
+        }
+    });
+    create_effect(move |_| {
+        let show = show_stats.get();
+        if let Some(storage) = local_storage() {
+            let _ = storage.set_item(SHOW_STATS_KEY, &show.to_string());
+        }
+    });
 
-    let ws_sender: Rc<RefCell<Option<futures_util::stream::SplitSink<WebSocket, WsMessage>>>> = Rc::new(RefCell::new(None));
-    let ws_sender = Rc::new(ws_sender);
+    let transport: Rc<RefCell<Option<Rc<Transport>>>> = Rc::new(RefCell::new(None));
+
+    // `/c/:id` opens a stored conversation with its last page of messages, or all of them
+    // when the URL fragment names a message to scroll to.
+    let params = use_params_map();
+    let location = use_location();
+    create_effect(move |_| {
+        let target = location.hash.with(|hash| Uuid::parse_str(hash.trim_start_matches('#')).ok());
+        let Some(id) = params.with(|p| p.get("id").and_then(|id| Uuid::parse_str(id).ok())) else { return };
+        spawn_local(async move {
+            let last = target.is_none().then_some(PAGE_SIZE);
+            match load_conversation(id, last).await {
+                Ok(loaded) => {
+                    set_has_older.set(last.is_some_and(|last| loaded.messages.len() >= last));
+                    set_knowledge_base_id.set(loaded.knowledge_base_id);
+                    set_conversation.set(loaded);
+                    jump_to.set(target);
+                }
+                Err(e) => logging::error!("failed to load conversation {id}: {e}"),
+            }
+        });
+    });
+
+    let load_older = create_action(move |_: &()| {
+        let (id, first) = conversation.with_untracked(|c| (c.id, c.messages.first().map(|m| m.id)));
+        async move {
+            match load_messages(id, first, PAGE_SIZE).await {
+                Ok(page) => {
+                    set_has_older.set(page.len() >= PAGE_SIZE);
+                    set_conversation.update(|c| {
+                        if c.id == id && c.messages.first().map(|m| m.id) == first {
+                            c.messages.splice(0..0, page);
+                        }
+                    });
+                }
+                Err(e) => {
+                    set_has_older.set(false);
+                    logging::error!("failed to load messages of conversation {id}: {e}");
+                }
+            }
+        }
+    });
 
     {
-        let ws_sender = ws_sender.clone();
+        let transport = transport.clone();
         create_effect(move |_| {
-            let location = web_sys::window().unwrap().location();
-            let hostname = location.hostname().expect("failed to retrieve origin hostname");
-            let ws_url = format!("ws://{hostname}:3000/ws");
-
-            let ws = WebSocket::open(&ws_url).expect("failed to establish WebSocket connection");
-            let (sender, mut receiver) = ws.split();
-
-            *ws_sender.borrow_mut() = Some(sender);
-
-            let set_conversation = set_conversation.clone();
-            let set_is_loading = set_is_loading.clone();
-
-            spawn_local(async move {
-                while let Some(msg) = receiver.next().await {
-                    match msg {
-                        Ok(WsMessage::Text(text)) => {
-                            set_conversation.update(|c| {
-                                if let Some(last_message) = c.messages.last_mut() {
-                                    if last_message.role == "assistant" {
-                                        last_message.content.push_str(&text);
-                                    } else {
-                                        c.messages.push(Message {
-                                            id: Uuid::new_v4(),
-                                            role: "assistant".to_string(),
-                                            content: text,
-                                            timestamp: Utc::now().timestamp().to_string(),
-                                        });
-                                    }
-                                }
-                            });
-                            set_is_loading.set(false);
-                        }
-                        Err(_) => {
-                            set_is_loading.set(false);
-                            break;
+            *transport.borrow_mut() = Some(Transport::connect(move |event| match event {
+                // Citations precede the answer's tokens, which are then appended to this message.
+                ChatEvent::Citations { citations } => set_conversation.update(|c| {
+                    c.messages.push(Message { citations, ..Message::new(Role::Assistant, String::new()) });
+                }),
+                // Tool steps are shown on the answer they lead to.
+                ChatEvent::ToolStep { step } => set_conversation.update(|c| {
+                    match c.messages.last_mut().filter(|m| m.role == Role::Assistant) {
+                        Some(last_message) => last_message.tool_steps.push(step),
+                        None => c.messages.push(Message { tool_steps: vec![step], ..Message::new(Role::Assistant, String::new()) }),
+                    }
+                }),
+                ChatEvent::Token { content } => {
+                    set_conversation.update(|c| {
+                        if let Some(last_message) = c.messages.last_mut() {
+                            if last_message.role == Role::Assistant {
+                                last_message.content.push_str(&content);
+                            } else {
+                                c.messages.push(Message::new(Role::Assistant, content));
+                            }
                         }
-                        _ => {}
+                    });
+                }
+                ChatEvent::Stats { metadata } => set_conversation.update(|c| {
+                    if let Some(last_message) = c.messages.last_mut().filter(|m| m.role == Role::Assistant) {
+                        last_message.metadata = metadata;
                     }
+                }),
+                ChatEvent::Done => set_is_loading.set(false),
+                ChatEvent::Error { message } => {
+                    logging::error!("chat error: {message}");
+                    notice.set(Some(message));
+                    set_is_loading.set(false);
                 }
-            });
+                ChatEvent::Notice { message } => notice.set(Some(message)),
+            }));
         });
     }
 
-    let send_message = create_action(move |new_message: &String| {
+    let command_transport = transport.clone();
+    let send_message = create_action(move |prompt: &Prompt| {
         let user_message = Message {
-            id: Uuid::new_v4(),
-            role: "user".to_string(),
-            content: new_message.clone(),
-            timestamp: Utc::now().timestamp().to_string(),
+            attachments: prompt.attachments.clone(),
+            ..Message::new(Role::User, prompt.content.clone())
         };
 
         set_conversation.update(|c| {
@@ -109,27 +183,120 @@ fn ChatPage() -> impl IntoView {
 
         set_is_loading.set(true);
 
-        let msg = new_message.clone();
-        let ws_sender = ws_sender.clone();
+        let request = ChatRequest {
+            conversation_id: conversation.get_untracked().id,
+            content: prompt.content.clone(),
+            attachments: prompt.attachments.iter().map(|a| a.id).collect(),
+            persona_id: persona_id.get_untracked(),
+            knowledge_base_id: knowledge_base_id.get_untracked(),
+        };
+        let transport = transport.borrow().clone();
         async move {
-            if let Some(sender) = ws_sender.borrow_mut().as_mut() {
-                sender.send(WsMessage::Text(msg))
-                    .await
-                    .map_err(|_| ServerFnError::ServerError("WebSocket send error".to_string()))
-            } else {
-                Err(ServerFnError::ServerError("WebSocket not connected".to_string()))
+            match transport {
+                Some(transport) => transport.send(ClientMessage::Chat(request)).await,
+                None => Err(ServerFnError::ServerError("Chat transport not connected".to_string())),
+            }
+        }
+    });
+
+    let run_command = create_action(move |action: &CommandAction| {
+        let conversation = conversation.get_untracked();
+        let mut message = None;
+        match action {
+            CommandAction::Session(command) => {
+                if *command == SessionCommand::Clear {
+                    set_conversation.update(|c| c.messages.clear());
+                    set_has_older.set(false);
+                }
+                message = Some(ClientMessage::Command(CommandRequest {
+                    conversation_id: conversation.id,
+                    persona_id: persona_id.get_untracked(),
+                    command: command.clone(),
+                }));
+            }
+            CommandAction::Export(_) if conversation.messages.is_empty() => {
+                notice.set(Some("Nothing to export yet.".to_string()));
+            }
+            CommandAction::Export(format) => {
+                let url = format!("/api/conversations/{}/export?format={format}", conversation.id);
+                let _ = window().location().set_href(&url);
+            }
+            CommandAction::Stats(show) => {
+                let show = show.unwrap_or(!show_stats.get_untracked());
+                show_stats.set(show);
+                let text = if show { "Generation stats are shown under answers." } else { "Generation stats are hidden." };
+                notice.set(Some(text.to_string()));
+            }
+            CommandAction::Notice(text) => notice.set(Some(text.clone())),
+        }
+
+        let transport = command_transport.borrow().clone();
+        async move {
+            if let (Some(message), Some(transport)) = (message, transport) {
+                if let Err(e) = transport.send(message).await {
+                    notice.set(Some(e.to_string()));
+                }
             }
         }
     });
 
     view! {
         <div class="min-h-screen w-full flex justify-center">
-            <ChatArea conversation/>
-            <PromptArea on_submit=send_message is_loading=is_loading/>
+            <div class="fixed top-2 left-4 z-10 flex gap-2">
+                <SearchBox/>
+                <Show when=move || conversation.with(|c| c.messages.is_empty())>
+                    <PersonaPicker persona_id set_persona_id/>
+                </Show>
+                <KnowledgePicker knowledge_base_id set_knowledge_base_id/>
+            </div>
+            <div class="fixed top-2 right-4 z-10 flex gap-2">
+                <ShareMenu conversation/>
+                <ExportMenu conversation/>
+            </div>
+            <ChatArea conversation has_older load_older jump_to show_stats/>
+            <PromptArea on_submit=send_message on_command=run_command notice is_loading=is_loading/>
         </div>
     }
 }
 
+fn local_storage() -> Option<web_sys::Storage> {
+    window().local_storage().ok().flatten()
+}
+
+/// Loads a conversation with only its `last` messages, or all of them.
+async fn load_conversation(id: Uuid, last: Option<usize>) -> Result<Conversation, String> {
+    let url = match last {
+        Some(last) => format!("/api/conversations/{id}?last={last}"),
+        None => format!("/api/conversations/{id}"),
+    };
+    let response = gloo_net::http::Request::get(&url)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    match response.ok() {
+        true => response.json().await.map_err(|e| e.to_string()),
+        false => Err(response.text().await.unwrap_or_else(|e| e.to_string())),
+    }
+}
+
+/// Loads up to `limit` of the messages before `before`, or the last ones.
+async fn load_messages(id: Uuid, before: Option<Uuid>, limit: usize) -> Result<Vec<Message>, String> {
+    let url = match before {
+        Some(before) => format!("/api/conversations/{id}/messages?before={before}&limit={limit}"),
+        None => format!("/api/conversations/{id}/messages?limit={limit}"),
+    };
+    let response = gloo_net::http::Request::get(&url)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    match response.ok() {
+        true => response.json().await.map_err(|e| e.to_string()),
+        false => Err(response.text().await.unwrap_or_else(|e| e.to_string())),
+    }
+}
+
 /// 404 - Not Found Component
 #[component]
 fn NotFound() -> impl IntoView {
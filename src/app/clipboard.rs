@@ -0,0 +1,96 @@
+//! Copying to the system clipboard. The async Clipboard API is used where the page
+//! may use it; over plain HTTP, where `navigator.clipboard` does not exist, or when the
+//! browser refuses, the text is copied through a hidden textarea and
+//! `document.execCommand("copy")` instead.
+
+use leptos::{document, window};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+
+const FAILED: &str = "Could not copy to the clipboard";
+
+/// Writes text to the system clipboard.
+pub async fn copy_text(text: &str) -> Result<(), String> {
+    copy(text, None).await
+}
+
+/// Writes Markdown to the system clipboard along with its rendered HTML, so pasting into
+/// a rich text editor keeps the formatting while plain text editors get the Markdown.
+/// Falls back to the Markdown alone where rich text can't be written.
+pub async fn copy_markdown(markdown: &str, html: &str) -> Result<(), String> {
+    copy(markdown, Some(html)).await
+}
+
+async fn copy(text: &str, html: Option<&str>) -> Result<(), String> {
+    let navigator = window().navigator();
+    let available = js_sys::Reflect::has(&navigator, &JsValue::from_str("clipboard")).unwrap_or(false);
+    if available && !write_denied().await {
+        if let Some(html) = html {
+            if write_rich(text, html).await.is_ok() {
+                return Ok(());
+            }
+        }
+        if JsFuture::from(navigator.clipboard().write_text(text)).await.is_ok() {
+            return Ok(());
+        }
+    }
+    copy_with_selection(text)
+}
+
+/// Whether the user has blocked clipboard writes. Browsers that can't be asked, such
+/// as Firefox, count as not blocked.
+async fn write_denied() -> bool {
+    let Ok(permissions) = window().navigator().permissions() else { return false };
+    let descriptor = js_sys::Object::new();
+    if js_sys::Reflect::set(&descriptor, &JsValue::from_str("name"), &JsValue::from_str("clipboard-write")).is_err() {
+        return false;
+    }
+    let Ok(query) = permissions.query(&descriptor) else { return false };
+    match JsFuture::from(query).await {
+        Ok(status) => js_sys::Reflect::get(&status, &JsValue::from_str("state"))
+            .ok()
+            .and_then(|state| state.as_string())
+            .is_some_and(|state| state == "denied"),
+        Err(_) => false,
+    }
+}
+
+/// Writes a `ClipboardItem` with plain text and HTML versions.
+async fn write_rich(text: &str, html: &str) -> Result<(), JsValue> {
+    let constructor = js_sys::Reflect::get(&window(), &JsValue::from_str("ClipboardItem"))?;
+    let constructor: js_sys::Function = constructor.dyn_into()?;
+
+    let data = js_sys::Object::new();
+    for (content_type, content) in [("text/plain", text), ("text/html", html)] {
+        let options = web_sys::BlobPropertyBag::new();
+        options.set_type(content_type);
+        let parts = js_sys::Array::of1(&JsValue::from_str(content));
+        let blob = web_sys::Blob::new_with_str_sequence_and_options(&parts, &options)?;
+        js_sys::Reflect::set(&data, &JsValue::from_str(content_type), &blob)?;
+    }
+    let item = js_sys::Reflect::construct(&constructor, &js_sys::Array::of1(&data))?;
+
+    JsFuture::from(window().navigator().clipboard().write(&js_sys::Array::of1(&item))).await?;
+    Ok(())
+}
+
+/// Copies by selecting the text in an off-screen textarea, which also works over plain HTTP.
+fn copy_with_selection(text: &str) -> Result<(), String> {
+    let document = document();
+    let textarea: web_sys::HtmlTextAreaElement = document.create_element("textarea")
+        .map_err(|_| FAILED.to_string())?
+        .unchecked_into();
+    textarea.set_value(text);
+    textarea.set_read_only(true);
+    let _ = textarea.set_attribute("style", "position: fixed; top: -1000px; opacity: 0");
+    let body = document.body().ok_or_else(|| FAILED.to_string())?;
+    body.append_child(&textarea).map_err(|_| FAILED.to_string())?;
+    textarea.select();
+
+    let copied = document.unchecked_ref::<web_sys::HtmlDocument>().exec_command("copy").unwrap_or(false);
+    textarea.remove();
+    match copied {
+        true => Ok(()),
+        false => Err("The browser blocked copying. Select the text and copy it manually.".to_string()),
+    }
+}
@@ -0,0 +1,127 @@
+//! Slash commands typed into the prompt area.
+//!
+//! Every command is an entry of [`COMMANDS`]: adding one only takes a new entry whose
+//! `parse` turns the text after the command name into a [`CommandAction`]. Actions that
+//! change session state are sent to the server as a `SessionCommand`; the others run in
+//! the browser.
+
+use crate::model::event::SessionCommand;
+
+pub struct Command {
+    pub name: &'static str,
+    pub usage: &'static str,
+    pub description: &'static str,
+    /// Parses the (trimmed) arguments following the command name.
+    pub parse: fn(&str) -> Result<CommandAction, String>,
+}
+
+/// What running a command does.
+pub enum CommandAction {
+    /// Changes the conversation's session on the server.
+    Session(SessionCommand),
+    /// Downloads the conversation in the given export format.
+    Export(&'static str),
+    /// Shows or hides the generation stats under answers, or toggles them.
+    Stats(Option<bool>),
+    /// Shows a message without contacting the server.
+    Notice(String),
+}
+
+pub const COMMANDS: &[Command] = &[
+    Command {
+        name: "help",
+        usage: "/help",
+        description: "List the available commands",
+        parse: |_| Ok(CommandAction::Notice(help())),
+    },
+    Command {
+        name: "model",
+        usage: "/model [name]",
+        description: "Show or switch the model of this conversation",
+        parse: |args| Ok(CommandAction::Session(SessionCommand::Model { model: optional(args) })),
+    },
+    Command {
+        name: "system",
+        usage: "/system [prompt]",
+        description: "Show or replace the system prompt of this conversation",
+        parse: |args| Ok(CommandAction::Session(SessionCommand::System { prompt: optional(args) })),
+    },
+    Command {
+        name: "temp",
+        usage: "/temp [value]",
+        description: "Show or set the sampling temperature",
+        parse: |args| {
+            let value = match optional(args) {
+                None => None,
+                Some(value) => match value.parse::<f32>() {
+                    Ok(value) if (0.0..=2.0).contains(&value) => Some(value),
+                    _ => return Err(format!("Temperature must be a number between 0 and 2, got `{value}`")),
+                },
+            };
+            Ok(CommandAction::Session(SessionCommand::Temperature { value }))
+        },
+    },
+    Command {
+        name: "clear",
+        usage: "/clear",
+        description: "Forget this conversation's messages",
+        parse: |_| Ok(CommandAction::Session(SessionCommand::Clear)),
+    },
+    Command {
+        name: "export",
+        usage: "/export [markdown|json|html]",
+        description: "Download this conversation",
+        parse: |args| match optional(args).as_deref() {
+            None | Some("markdown") | Some("md") => Ok(CommandAction::Export("markdown")),
+            Some("json") => Ok(CommandAction::Export("json")),
+            Some("html") => Ok(CommandAction::Export("html")),
+            Some(other) => Err(format!("Unknown export format `{other}`")),
+        },
+    },
+    Command {
+        name: "stats",
+        usage: "/stats [on|off]",
+        description: "Show or hide the generation stats under answers",
+        parse: |args| match optional(args).as_deref() {
+            None => Ok(CommandAction::Stats(None)),
+            Some("on") => Ok(CommandAction::Stats(Some(true))),
+            Some("off") => Ok(CommandAction::Stats(Some(false))),
+            Some(other) => Err(format!("Expected `on` or `off`, got `{other}`")),
+        },
+    },
+];
+
+/// Splits `/name args` into its command and parses the arguments. `None` when the
+/// input is not a command; a leading `//` sends the rest as a normal message.
+pub fn parse(input: &str) -> Option<Result<CommandAction, String>> {
+    let rest = input.strip_prefix('/').filter(|rest| !rest.starts_with('/'))?;
+    let (name, args) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+
+    Some(match COMMANDS.iter().find(|command| command.name == name) {
+        Some(command) => (command.parse)(args.trim()),
+        None => Err(format!("Unknown command /{name}. Type /help to list the commands.")),
+    })
+}
+
+/// Commands whose name starts with what has been typed after the `/`, while the
+/// name is still being typed.
+pub fn completions(input: &str) -> Vec<&'static Command> {
+    match input.strip_prefix('/') {
+        Some(prefix) if !prefix.contains(char::is_whitespace) && !prefix.starts_with('/') => {
+            COMMANDS.iter().filter(|command| command.name.starts_with(prefix)).collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn optional(args: &str) -> Option<String> {
+    Some(args.to_string()).filter(|args| !args.is_empty())
+}
+
+fn help() -> String {
+    COMMANDS
+        .iter()
+        .map(|command| format!("{} — {}", command.usage, command.description))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,82 @@
+use crate::model::attachment::Attachment;
+use gloo_net::http::Request;
+use leptos::*;
+
+/// Renders an attachment as a chip linking to the original file, or as a
+/// thumbnail for images, with a remove button while it is still part of an
+/// unsent prompt.
+#[component]
+pub fn AttachmentChip(
+    attachment: Attachment,
+    #[prop(optional, into)] on_remove: Option<Callback<()>>,
+) -> impl IntoView {
+    let href = format!("/api/attachments/{}", attachment.id);
+    let title = match attachment.truncated {
+        true => format!("{} ({}, cut to fit the context)", attachment.name, format_size(attachment.size)),
+        false => format!("{} ({})", attachment.name, format_size(attachment.size)),
+    };
+
+    if attachment.is_image() {
+        return view! {
+            <span class="relative inline-block">
+                <a href=href.clone() target="_blank" title=title>
+                    <img
+                        src=href
+                        alt=attachment.name
+                        loading="lazy"
+                        class="h-16 w-16 rounded-md border dark:border-gray-600 object-cover"
+                    />
+                </a>
+                {on_remove.map(|on_remove| view! {
+                    <button
+                        type="button"
+                        class="absolute -top-1.5 -right-1.5 h-4 w-4 rounded-full bg-gray-700 text-white text-xs leading-none hover:bg-red-500"
+                        on:click=move |_| on_remove.call(())
+                    >
+                        "×"
+                    </button>
+                })}
+            </span>
+        }.into_view();
+    }
+
+    view! {
+        <span class="inline-flex items-center gap-1 max-w-[16rem] rounded-md border dark:border-gray-600 bg-gray-50 dark:bg-gray-700 px-2 py-0.5 text-xs">
+            <a href=href target="_blank" class="truncate hover:underline" title=title>
+                {attachment.name}
+            </a>
+            {attachment.truncated.then(|| view! { <span class="text-amber-600" title="Cut to fit the context">"✂"</span> })}
+            {on_remove.map(|on_remove| view! {
+                <button type="button" class="text-gray-500 hover:text-red-500" on:click=move |_| on_remove.call(())>
+                    "×"
+                </button>
+            })}
+        </span>
+    }.into_view()
+}
+
+/// Uploads a picked or dropped file, returning the stored attachment.
+pub async fn upload_attachment(file: web_sys::File) -> Result<Attachment, String> {
+    let name = file.name();
+    let url = format!("/api/attachments?name={}", js_sys::encode_uri_component(&name));
+    let response = Request::post(&url)
+        .header("Content-Type", &file.type_())
+        .body(file)
+        .map_err(|e| e.to_string())?
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    match response.ok() {
+        true => response.json().await.map_err(|e| e.to_string()),
+        false => Err(format!("{name}: {}", response.text().await.unwrap_or_else(|e| e.to_string()))),
+    }
+}
+
+fn format_size(bytes: usize) -> String {
+    match bytes {
+        0..=1023 => format!("{bytes} B"),
+        1024..=1_048_575 => format!("{:.1} KB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MB", bytes as f64 / 1_048_576.0),
+    }
+}
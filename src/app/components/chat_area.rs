@@ -1,55 +1,311 @@
-use crate::model::conversation::Conversation;
+use crate::app::components::attachments::AttachmentChip;
+use crate::model::attachment::Attachment;
+use crate::model::conversation::{Conversation, MessageMetadata, Role};
+use crate::model::knowledge::Citation;
+use crate::model::tool::{ToolStep, ToolTable};
+use crate::app::clipboard;
 use leptos::{html::Div, *};
-use pulldown_cmark::{html, Options, Parser};
+use std::collections::HashMap;
+use crate::markdown;
+use wasm_bindgen::JsCast;
+use uuid::Uuid;
 
-/// Renders the chat area of the chat page.
+/// Height a message is assumed to have until it has been rendered and measured.
+const ESTIMATED_HEIGHT: f64 = 150.0;
+/// How far above and below the screen messages are rendered, in pixels.
+const OVERSCAN: f64 = 1200.0;
+
+/// How close to the bottom of the page the reader has to be for new content to keep
+/// the page scrolled to the bottom.
+const STICK_DISTANCE: f64 = 80.0;
+
+/// The messages that are rendered, and the height of those above and below them.
+#[derive(Clone, Copy, Default, PartialEq)]
+struct Window {
+    start: usize,
+    end: usize,
+    above: f64,
+    below: f64,
+}
+
+/// Renders the chat area of the chat page. Only the messages on or near the screen are
+/// rendered, with spacers standing in for the others at their measured height, or an
+/// estimate for messages not rendered yet. While the conversation has older messages
+/// than those loaded, scrolling near the top loads the next page of them.
+///
+/// New content keeps the page scrolled to the bottom only while the reader is there;
+/// otherwise it is announced by a button that jumps to it.
+///
+/// Answers show how they were generated under them while `show_stats` is set.
 #[component]
-pub fn ChatArea(conversation: ReadSignal<Conversation>) -> impl IntoView {
+pub fn ChatArea(
+    conversation: ReadSignal<Conversation>,
+    #[prop(into)] has_older: Signal<bool>,
+    load_older: Action<(), ()>,
+    jump_to: RwSignal<Option<Uuid>>,
+    show_stats: RwSignal<bool>,
+) -> impl IntoView {
     let chat_div_ref = create_node_ref::<Div>();
+    let at_bottom = create_rw_signal(true);
+    let (unseen, set_unseen) = create_signal(false);
+
+    let list_ref = create_node_ref::<Div>();
+    // How far the top of the screen is below the top of the list, and the screen's height.
+    let (viewport, set_viewport) = create_signal((0.0, 0.0));
+    let heights = create_rw_signal(HashMap::<Uuid, f64>::new());
+    let height_of = move |heights: &HashMap<Uuid, f64>, id: &Uuid| heights.get(id).copied().unwrap_or(ESTIMATED_HEIGHT);
+
+    let update_viewport = move || {
+        if let Some(list) = list_ref.get_untracked() {
+            let height = window().inner_height().ok().and_then(|height| height.as_f64()).unwrap_or_default();
+            set_viewport.set((-list.get_bounding_client_rect().top(), height));
+        }
+    };
+    let on_scroll = window_event_listener(ev::scroll, move |_| {
+        update_viewport();
+        at_bottom.set(distance_to_bottom() < STICK_DISTANCE);
+        if at_bottom.get_untracked() {
+            set_unseen.set(false);
+        }
+    });
+    let on_resize = window_event_listener(ev::resize, move |_| update_viewport());
+    on_cleanup(move || {
+        on_scroll.remove();
+        on_resize.remove();
+    });
+
+    let range = create_memo(move |_| {
+        let (top, screen) = viewport.get();
+        heights.with(|heights| conversation.with(|c| {
+            let mut range = Window::default();
+            let mut offset = 0.0;
+            for (i, message) in c.messages.iter().enumerate() {
+                let height = height_of(heights, &message.id);
+                if offset + height < top - OVERSCAN {
+                    range.above += height;
+                    range.start = i + 1;
+                } else if offset > top + screen + OVERSCAN {
+                    range.below += height;
+                } else {
+                    range.end = i + 1;
+                }
+                offset += height;
+            }
+            range.end = range.end.max(range.start);
+            range
+        }))
+    });
+
+    // Measures the rendered messages once laid out. Content above the screen changing
+    // height, such as a message replacing its estimate, would push what is on screen
+    // up or down, so the page is scrolled by the difference.
+    create_effect(move |_| {
+        range.track();
+        conversation.track();
+        request_animation_frame(move || {
+            let Some(items) = chat_div_ref.get_untracked() else { return };
+            let children = items.children();
+            let mut shift = 0.0;
+            let mut measured = Vec::new();
+            for i in 0..children.length() {
+                let Some(child) = children.item(i) else { continue };
+                let Some(id) = child.get_attribute("id").and_then(|id| Uuid::parse_str(&id).ok()) else { continue };
+                let rect = child.get_bounding_client_rect();
+                let change = rect.height() - heights.with_untracked(|heights| height_of(heights, &id));
+                if change.abs() > 0.5 {
+                    if rect.top() < 0.0 {
+                        shift += change;
+                    }
+                    measured.push((id, rect.height()));
+                }
+            }
+            if !measured.is_empty() {
+                heights.update(|heights| heights.extend(measured));
+            }
+            if at_bottom.get_untracked() {
+                scroll_to_bottom();
+            } else if shift != 0.0 {
+                window().scroll_by_with_x_and_y(0.0, shift);
+            }
+            update_viewport();
+        });
+    });
+
+    // A new message or token shows the button unless the page follows it; opening
+    // another conversation starts at its latest message.
+    let latest = create_memo(move |_| conversation.with(|c| {
+        (c.id, c.messages.last().map(|m| (m.id, m.content.len(), m.tool_steps.len())))
+    }));
+    create_effect(move |previous: Option<Uuid>| {
+        let (id, _) = latest.get();
+        if previous == Some(id) {
+            if !at_bottom.get_untracked() {
+                set_unseen.set(true);
+            }
+        } else {
+            at_bottom.set(true);
+            set_unseen.set(false);
+        }
+        id
+    });
+
+    // Keeps the messages on screen in place when older ones are loaded above them.
+    create_effect(move |previous: Option<Option<Uuid>>| {
+        let first = conversation.with(|c| c.messages.first().map(|m| m.id));
+        if let Some(Some(previous)) = previous.filter(|previous| *previous != first) {
+            let added: f64 = conversation.with_untracked(|c| heights.with_untracked(|heights| {
+                c.messages.iter().take_while(|m| m.id != previous).map(|m| height_of(heights, &m.id)).sum()
+            }));
+            // An older page is added in front of the first message; anything else is a different conversation.
+            if added > 0.0 && conversation.with_untracked(|c| c.messages.iter().any(|m| m.id == previous)) {
+                request_animation_frame(move || {
+                    window().scroll_by_with_x_and_y(0.0, added);
+                    update_viewport();
+                });
+            }
+        }
+        first
+    });
+
+    create_effect(move |_| {
+        let near_top = viewport.with(|(top, _)| *top < OVERSCAN) && range.with(|range| range.start == 0);
+        if near_top && has_older.get() && !load_older.pending().get() {
+            load_older.dispatch(());
+        }
+    });
+
+    // Scrolls to a message, e.g. one a search result links to, by first bringing it
+    // into the rendered range at its estimated position.
     create_effect(move |_| {
-        conversation.get();
-        if let Some(div) = chat_div_ref.get() {
-            div.set_scroll_top(div.scroll_height());
+        let Some(id) = jump_to.get() else { return };
+        let offset: Option<f64> = conversation.with_untracked(|c| heights.with_untracked(|heights| {
+            let index = c.messages.iter().position(|m| m.id == id)?;
+            Some(c.messages[..index].iter().map(|m| height_of(heights, &m.id)).sum())
+        }));
+        let Some(offset) = offset else { return };
+        at_bottom.set(false);
+        if let Some(list) = list_ref.get_untracked() {
+            let page_top = window().scroll_y().unwrap_or_default();
+            window().scroll_to_with_x_and_y(0.0, page_top + list.get_bounding_client_rect().top() + offset);
+            update_viewport();
         }
+        request_animation_frame(move || {
+            if let Some(element) = document().get_element_by_id(&id.to_string()) {
+                element.scroll_into_view();
+            }
+            jump_to.set(None);
+        });
     });
 
     view! {
         <div class="py-2.5 flex flex-col justify-between w-full">
             <div class="h-full mt-10 mb-32 w-full flex flex-col">
-                <div class="w-full">
+                // The browser's scroll anchoring would fight the adjustments above.
+                <div class="w-full" style="overflow-anchor: none" node_ref=list_ref>
+                    <div style:height=move || format!("{}px", range.with(|range| range.above))></div>
                     <div class="flex justify-between px-5 mb-3 max-w-4xl mx-auto rounded-lg group flex-col" node_ref=chat_div_ref>
-                        {move || conversation.get().messages.iter().map(move |message| {
-                            view! {
-                            <Message
-                                message=message.content.clone()
-                            />
+                        // Messages are keyed so only new ones are rendered; a streamed answer
+                        // follows its content through a memo, and is rendered again when a
+                        // tool step is added to it.
+                        <For
+                            each=move || {
+                                let Window { start, end, .. } = range.get();
+                                conversation.with(|c| {
+                                    c.messages[start.min(c.messages.len())..end.min(c.messages.len())]
+                                        .iter()
+                                        .map(|m| (m.id, m.tool_steps.len()))
+                                        .collect::<Vec<_>>()
+                                })
                             }
-                        }).collect::<Vec<_>>()}
+                            key=|key| *key
+                            children=move |(id, _)| {
+                                let Some(message) = conversation.with_untracked(|c| c.messages.iter().find(|m| m.id == id).cloned()) else {
+                                    return ().into_view();
+                                };
+                                let content = create_memo(move |_| conversation.with(|c| {
+                                    c.messages.iter().rev().find(|m| m.id == id).map(|m| m.content.clone()).unwrap_or_default()
+                                }));
+                                let metadata = create_memo(move |_| conversation.with(|c| {
+                                    c.messages.iter().rev().find(|m| m.id == id).map(|m| m.metadata.clone()).unwrap_or_default()
+                                }));
+                                view! {
+                                    <Message
+                                        id
+                                        role=message.role
+                                        message=content
+                                        metadata
+                                        show_stats
+                                        attachments=message.attachments
+                                        citations=message.citations
+                                        tool_steps=message.tool_steps
+                                    />
+                                }.into_view()
+                            }
+                        />
                     </div>
+                    <div style:height=move || format!("{}px", range.with(|range| range.below))></div>
                 </div>
             </div>
+            <Show when=move || unseen.get()>
+                <button
+                    on:click=move |_| {
+                        at_bottom.set(true);
+                        set_unseen.set(false);
+                        scroll_to_bottom();
+                    }
+                    class="fixed bottom-36 left-1/2 -translate-x-1/2 z-10 px-3 py-1.5 rounded-full text-sm shadow-md bg-gray-800 text-white dark:bg-gray-100 dark:text-gray-800 hover:opacity-90"
+                >
+                    "New content ↓"
+                </button>
+            </Show>
         </div>
     }
 }
 
-/// Renders the Message component for the ChatArea.
-#[component]
-fn Message(message: String) -> impl IntoView {
-    let (is_btn_pressed, set_is_btn_pressed) = create_signal(false);
+/// How far the bottom of the page is below the bottom of the screen.
+fn distance_to_bottom() -> f64 {
+    let window = window();
+    let page_height = document().document_element().map_or(0, |root| root.scroll_height()) as f64;
+    let scrolled = window.scroll_y().unwrap_or_default();
+    let screen = window.inner_height().ok().and_then(|height| height.as_f64()).unwrap_or_default();
+    page_height - scrolled - screen
+}
 
-    create_effect(move |_| {
-        if is_btn_pressed.get() {
-            set_timeout(
-                move || {
-                    set_is_btn_pressed.set(false);
-                },
-                2000,
-            );
-        }
-    });
+fn scroll_to_bottom() {
+    let page_height = document().document_element().map_or(0, |root| root.scroll_height()) as f64;
+    window().scroll_to_with_x_and_y(0.0, page_height);
+}
+
+/// Renders the Message component for the ChatArea. The `[n]` markers of an answer
+/// link to the knowledge base chunks listed under it, and the tool calls that led
+/// to it are shown collapsed above it.
+#[component]
+fn Message(
+    id: Uuid,
+    role: Role,
+    #[prop(into)] message: Signal<String>,
+    #[prop(into)] metadata: Signal<MessageMetadata>,
+    show_stats: RwSignal<bool>,
+    attachments: Vec<Attachment>,
+    citations: Vec<Citation>,
+    tool_steps: Vec<ToolStep>,
+) -> impl IntoView {
+    // The outcome of the last copy, shown on the button for a moment.
+    let (copied, set_copied) = create_signal(None::<Result<(), String>>);
+    let copy = move |_| {
+        let message = message.get_untracked();
+        spawn_local(async move {
+            let result = clipboard::copy_markdown(&message, &markdown::render(&message)).await;
+            if let Err(e) = &result {
+                logging::error!("failed to copy message: {e}");
+            }
+            set_copied.set(Some(result));
+            set_timeout(move || set_copied.set(None), 2000);
+        });
+    };
 
     view! {
-        <div class="group w-full text-gray-800 dark:text-gray-100 border-b border-gray-700 last:border-b-0 text-balance">
+        <div id=id.to_string() class="group w-full text-gray-800 dark:text-gray-100 border-b border-gray-700 last:border-b-0 text-balance scroll-mt-14">
             <div class="text-base gap-4 md:gap-6 flex lg:px-0 m-auto w-full text-balance">
                 <div class="flex flex-row gap-4 md:gap-6 p-4 md:py-6 lg:px-0 m-auto w-full text-balance">
                     <div class="w-8 flex flex-col relative items-end">
@@ -59,19 +315,54 @@ fn Message(message: String) -> impl IntoView {
                     </div>
                     <div class="relative flex w-[calc(100%-50px)] flex-row gap-1 md:gap-3 lg:w-[calc(100%-115px)]">
                         <div class="flex flex-grow flex-col gap-3 text-balance w-full">
-                            <Markdown markdown=message />
+                            {(!attachments.is_empty()).then(|| view! {
+                                <div class="flex flex-wrap items-start gap-1">
+                                    {attachments.into_iter().map(|attachment| view! { <AttachmentChip attachment/> }).collect_view()}
+                                </div>
+                            })}
+                            {tool_steps.into_iter().map(|step| view! { <ToolStepDetails step/> }).collect_view()}
+                            <Markdown markdown=message links=citation_links(&citations) />
+                            {(!citations.is_empty()).then(|| view! {
+                                <ol class="flex flex-col gap-0.5 text-xs text-gray-500">
+                                    {citations.into_iter().map(|citation| view! {
+                                        <li>
+                                            <a href=citation.href() target="_blank" class="hover:underline" title=citation.excerpt.clone()>
+                                                {format!("[{}] {}, part {}", citation.number, citation.document_name, citation.chunk + 1)}
+                                            </a>
+                                        </li>
+                                    }).collect_view()}
+                                </ol>
+                            })}
+                            {move || {
+                                let stats = metadata.with(stats_line);
+                                (role == Role::Assistant && show_stats.get() && !stats.is_empty()).then(|| view! {
+                                    <div class="flex items-center gap-2 text-xs text-gray-500">
+                                        <span>{stats}</span>
+                                        <button
+                                            on:click=move |_| show_stats.set(false)
+                                            title="Hide generation stats (/stats shows them again)"
+                                            class="opacity-0 group-hover:opacity-100 hover:text-gray-800 dark:hover:text-gray-200"
+                                        >
+                                            "×"
+                                        </button>
+                                    </div>
+                                })
+                            }}
                         </div>
                         <div class="flex space-x-2">
                             <button
-                                on:click=move |_| {
-                                    set_is_btn_pressed.set(true);
+                                on:click=copy
+                                title=move || match copied.get() {
+                                    Some(Err(e)) => e,
+                                    Some(Ok(())) => "Copied".to_string(),
+                                    None => "Copy message".to_string(),
                                 }
                                 class="flex items-center justify-center w-8 h-8 rounded-full bg-gray-100 dark:bg-gray-800 hover:bg-gray-200 dark:hover:bg-gray-700 transition-colors duration-200 focus:outline-none focus:ring-2 focus:ring-offset-2 focus:ring-gray-500"
                             >
-                                {move || if !is_btn_pressed.get() {
-                                    view! { <ClipboardIcon /> }
-                                } else {
-                                    view! { <CheckIcon /> }
+                                {move || match copied.get() {
+                                    None => view! { <ClipboardIcon /> }.into_view(),
+                                    Some(Ok(())) => view! { <CheckIcon /> }.into_view(),
+                                    Some(Err(_)) => view! { <span class="text-sm font-bold text-red-500">"!"</span> }.into_view(),
                                 }}
                             </button>
                         </div>
@@ -82,26 +373,160 @@ fn Message(message: String) -> impl IntoView {
     }
 }
 
-/// Renders the Markdown Parser.
+/// Summarizes how an answer was generated, e.g. "llama3.1 · 212 tokens · 48.3 tok/s ·
+/// 0.41 s to first token · stop". Empty until the answer is complete.
+fn stats_line(metadata: &MessageMetadata) -> String {
+    let mut parts = Vec::new();
+    parts.extend(metadata.model.clone());
+    parts.extend(metadata.completion_tokens.map(|tokens| format!("{tokens} tokens")));
+    parts.extend(metadata.tokens_per_second().map(|rate| format!("{rate:.1} tok/s")));
+    parts.extend(metadata.time_to_first_token_ms.map(|ms| format!("{:.2} s to first token", ms as f64 / 1000.0)));
+    parts.extend(metadata.finish_reason.clone());
+    parts.join(" · ")
+}
+
+/// Renders one tool call with its arguments and result. Rows a tool returned are
+/// shown as a table under it.
 #[component]
-pub fn Markdown(markdown: String) -> impl IntoView {
-    let parsed_html = create_memo(move |_| {
-        let mut options = Options::empty();
-        options.insert(Options::ENABLE_STRIKETHROUGH);
-        options.insert(Options::ENABLE_TABLES);
+fn ToolStepDetails(step: ToolStep) -> impl IntoView {
+    let arguments = serde_json::to_string_pretty(&step.arguments).unwrap_or_default();
 
-        let parser = Parser::new_ext(&markdown, options);
-        let mut html_output = String::new();
-        html::push_html(&mut html_output, parser);
+    view! {
+        <details class="text-sm rounded-md border dark:border-gray-700">
+            <summary class="cursor-pointer px-2 py-1 text-gray-500" class=("text-red-500", step.is_error)>
+                {format!("🔧 {}", step.name)}
+                {step.is_error.then_some(" (failed)")}
+            </summary>
+            <div class="flex flex-col gap-1 px-2 pb-2">
+                <pre class="whitespace-pre-wrap text-xs">{arguments}</pre>
+                <pre class="whitespace-pre-wrap text-xs" class=("text-red-500", step.is_error)>{step.result}</pre>
+            </div>
+        </details>
+        {step.table.map(|table| view! { <ResultTable table/> })}
+    }
+}
 
-        html_output
-    });
+#[component]
+fn ResultTable(table: ToolTable) -> impl IntoView {
+    let column_count = table.columns.len();
+    let is_empty = table.rows.is_empty();
 
     view! {
-        <div class="markdown-body" inner_html=parsed_html/>
+        <div class="overflow-x-auto max-h-96 text-xs rounded-md border dark:border-gray-700">
+            <table class="min-w-full">
+                <thead class="sticky top-0 bg-gray-100 dark:bg-gray-800">
+                    <tr>
+                        {table.columns.into_iter().map(|column| view! {
+                            <th class="px-2 py-1 text-left font-semibold whitespace-nowrap">{column}</th>
+                        }).collect_view()}
+                    </tr>
+                </thead>
+                <tbody class="divide-y dark:divide-gray-700">
+                    {table.rows.into_iter().map(|row| view! {
+                        <tr>
+                            {row.into_iter().map(|cell| match cell {
+                                Some(cell) => view! { <td class="px-2 py-1 whitespace-nowrap">{cell}</td> },
+                                None => view! { <td class="px-2 py-1 text-gray-400">"NULL"</td> },
+                            }).collect_view()}
+                        </tr>
+                    }).collect_view()}
+                    {is_empty.then(|| view! {
+                        <tr><td class="px-2 py-1 text-gray-500" colspan=column_count>"No rows"</td></tr>
+                    })}
+                </tbody>
+            </table>
+            {table.truncated.then(|| view! {
+                <p class="px-2 py-1 text-gray-500">"Only the first rows are shown."</p>
+            })}
+        </div>
     }
 }
 
+/// Renders Markdown a block at a time (see `markdown::blocks`), so while a message is
+/// streamed only its last block is parsed and rendered again; the blocks before it
+/// keep their HTML and DOM nodes, unless a link reference definition was added. `links`
+/// are further definitions, one per line, that every block is rendered with. Clicks on
+/// the code block toolbars that `markdown::render` adds are handled here, as the HTML
+/// is set as a whole.
+#[component]
+pub fn Markdown(#[prop(into)] markdown: MaybeSignal<String>, #[prop(optional)] links: String) -> impl IntoView {
+    let blocks = create_memo(move |previous: Option<&(String, Vec<(String, String)>)>| markdown.with(|markdown| {
+        let markdown = markdown::without_partial_fence(markdown);
+        let definitions = [markdown::link_definitions(markdown), links.clone()].join("\n").trim().to_string();
+        let previous = previous.filter(|(previous, _)| *previous == definitions).map(|(_, blocks)| blocks);
+        let blocks = markdown::blocks(markdown)
+            .into_iter()
+            .enumerate()
+            .map(|(i, source)| match previous.and_then(|previous| previous.get(i)).filter(|(previous, _)| previous == source) {
+                Some(block) => block.clone(),
+                None => (source.to_string(), markdown::render_block(source, &definitions)),
+            })
+            .collect::<Vec<_>>();
+        (definitions, blocks)
+    }));
+
+    view! {
+        <div class="markdown-body" on:click=code_block_action>
+            <For
+                each=move || blocks.with(|(_, blocks)| blocks.clone()).into_iter().enumerate()
+                key=|(i, (_, html))| (*i, html.clone())
+                children=|(_, (_, html))| view! { <div inner_html=html/> }
+            />
+        </div>
+    }
+}
+
+fn code_block_action(ev: ev::MouseEvent) {
+    let Some(button) = ev.target()
+        .and_then(|target| target.dyn_into::<web_sys::Element>().ok())
+        .and_then(|target| target.closest("[data-code-action]").ok().flatten())
+    else {
+        return;
+    };
+    let Some(block) = button.closest(".code-block").ok().flatten() else { return };
+    let code = block.query_selector("code").ok().flatten().and_then(|code| code.text_content()).unwrap_or_default();
+
+    match button.get_attribute("data-code-action").as_deref() {
+        Some("copy") => spawn_local(async move {
+            let label = match clipboard::copy_text(&code).await {
+                Ok(()) => "Copied",
+                Err(e) => {
+                    logging::error!("failed to copy code: {e}");
+                    "Copy failed"
+                }
+            };
+            button.set_text_content(Some(label));
+            set_timeout(move || button.set_text_content(Some("Copy")), 2000);
+        }),
+        Some("download") => {
+            let extension = block.get_attribute("data-extension").unwrap_or_else(|| "txt".to_string());
+            download(&format!("snippet.{extension}"), &code);
+        }
+        Some("wrap") => {
+            let wrapped = block.toggle_attribute("data-wrap").unwrap_or(false);
+            button.set_text_content(Some(if wrapped { "No wrap" } else { "Wrap" }));
+        }
+        _ => {}
+    }
+}
+
+/// Saves text as a file through a temporary `download` link.
+fn download(name: &str, text: &str) {
+    let Ok(link) = document().create_element("a") else { return };
+    let href = format!("data:text/plain;charset=utf-8,{}", js_sys::encode_uri_component(text));
+    if link.set_attribute("href", &href).is_ok() && link.set_attribute("download", name).is_ok() {
+        if let Ok(link) = link.dyn_into::<web_sys::HtmlElement>() {
+            link.click();
+        }
+    }
+}
+
+/// A Markdown link reference definition per citation, which turns the answer's
+/// `[1]`-style markers into links to the cited chunks.
+fn citation_links(citations: &[Citation]) -> String {
+    citations.iter().map(|c| format!("[{}]: {}", c.number, c.href())).collect::<Vec<_>>().join("\n")
+}
+
 /// SVG ClipBoard Component.
 #[component]
 fn ClipboardIcon() -> impl IntoView {
@@ -0,0 +1,38 @@
+use crate::model::conversation::Conversation;
+use leptos::*;
+
+const FORMATS: [(&str, &str); 3] = [("Markdown", "markdown"), ("JSON", "json"), ("HTML", "html")];
+
+/// Renders the export menu of the chat page.
+#[component]
+pub fn ExportMenu(conversation: ReadSignal<Conversation>) -> impl IntoView {
+    let link_class = "block px-3 py-1.5 hover:bg-gray-100 dark:hover:bg-gray-700";
+
+    view! {
+        <details class="relative text-sm">
+            <summary class="cursor-pointer list-none rounded-lg px-3 py-1.5 bg-gray-100 dark:bg-gray-700 hover:bg-gray-200 dark:hover:bg-gray-600">
+                "Export"
+            </summary>
+            <div class="absolute right-0 mt-1 w-56 rounded-lg border dark:border-gray-600 bg-white dark:bg-gray-800 shadow-lg py-1">
+                <Show when=move || !conversation.get().messages.is_empty()>
+                    <p class="px-3 py-1 text-xs text-gray-500">"This conversation"</p>
+                    {FORMATS.map(|(label, format)| view! {
+                        <a
+                            class=link_class
+                            href=move || format!("/api/conversations/{}/export?format={format}", conversation.get().id)
+                            download=""
+                        >
+                            {label}
+                        </a>
+                    })}
+                </Show>
+                <p class="px-3 py-1 text-xs text-gray-500">"All conversations"</p>
+                {FORMATS.map(|(label, format)| view! {
+                    <a class=link_class href=format!("/api/conversations/export?format={format}") download="">
+                        {label}
+                    </a>
+                })}
+            </div>
+        </details>
+    }
+}
@@ -0,0 +1,117 @@
+use crate::model::knowledge::{KnowledgeBase, KnowledgeDocument, SyncReport};
+use gloo_net::http::Request;
+use leptos::*;
+use serde_json::json;
+use uuid::Uuid;
+
+/// Renders the knowledge base selector of the chat page. Prompts are answered from
+/// the selected knowledge base; it is hidden until one has been created.
+#[component]
+pub fn KnowledgePicker(
+    knowledge_base_id: ReadSignal<Option<Uuid>>,
+    set_knowledge_base_id: WriteSignal<Option<Uuid>>,
+) -> impl IntoView {
+    let knowledge_bases = create_local_resource(|| (), |_| list_knowledge_bases());
+
+    view! {
+        <Transition fallback=|| ()>
+            {move || knowledge_bases.get().and_then(Result::ok).filter(|k| !k.is_empty()).map(|knowledge_bases| view! {
+                <select
+                    class="rounded-lg border dark:border-gray-600 bg-gray-100 dark:bg-gray-700 px-2 py-1.5 text-sm"
+                    title="Answer from a knowledge base"
+                    on:change=move |ev| set_knowledge_base_id.set(Uuid::parse_str(&event_target_value(&ev)).ok())
+                >
+                    <option value="" selected=move || knowledge_base_id.get().is_none()>"No knowledge base"</option>
+                    {knowledge_bases.into_iter().map(|knowledge_base| {
+                        let id = knowledge_base.id;
+                        view! {
+                            <option value=id.to_string() selected=move || knowledge_base_id.get() == Some(id)>
+                                {knowledge_base.name}
+                            </option>
+                        }
+                    }).collect_view()}
+                </select>
+            })}
+        </Transition>
+    }
+}
+
+pub async fn list_knowledge_bases() -> Result<Vec<KnowledgeBase>, String> {
+    let response = Request::get("/api/knowledge")
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    match response.ok() {
+        true => response.json().await.map_err(|e| e.to_string()),
+        false => Err(response.text().await.unwrap_or_else(|e| e.to_string())),
+    }
+}
+
+pub async fn create_knowledge_base(name: String, directory: Option<String>) -> Result<KnowledgeBase, String> {
+    let response = Request::post("/api/knowledge")
+        .json(&json!({ "name": name, "directory": directory }))
+        .map_err(|e| e.to_string())?
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    match response.ok() {
+        true => response.json().await.map_err(|e| e.to_string()),
+        false => Err(response.text().await.unwrap_or_else(|e| e.to_string())),
+    }
+}
+
+pub async fn delete_knowledge_base(id: Uuid) -> Result<(), String> {
+    let response = Request::delete(&format!("/api/knowledge/{id}"))
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    match response.ok() {
+        true => Ok(()),
+        false => Err(response.text().await.unwrap_or_else(|e| e.to_string())),
+    }
+}
+
+/// Uploads a document to a knowledge base, which chunks and embeds it before replying.
+pub async fn upload_document(id: Uuid, file: web_sys::File) -> Result<KnowledgeDocument, String> {
+    let name = file.name();
+    let url = format!("/api/knowledge/{id}/documents?name={}", js_sys::encode_uri_component(&name));
+    let response = Request::post(&url)
+        .header("Content-Type", &file.type_())
+        .body(file)
+        .map_err(|e| e.to_string())?
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    match response.ok() {
+        true => response.json().await.map_err(|e| e.to_string()),
+        false => Err(format!("{name}: {}", response.text().await.unwrap_or_else(|e| e.to_string()))),
+    }
+}
+
+pub async fn delete_document(id: Uuid, document_id: Uuid) -> Result<(), String> {
+    let response = Request::delete(&format!("/api/knowledge/{id}/documents/{document_id}"))
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    match response.ok() {
+        true => Ok(()),
+        false => Err(response.text().await.unwrap_or_else(|e| e.to_string())),
+    }
+}
+
+pub async fn sync_knowledge_base(id: Uuid) -> Result<SyncReport, String> {
+    let response = Request::post(&format!("/api/knowledge/{id}/sync"))
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    match response.ok() {
+        true => response.json().await.map_err(|e| e.to_string()),
+        false => Err(response.text().await.unwrap_or_else(|e| e.to_string())),
+    }
+}
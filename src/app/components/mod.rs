@@ -1,2 +1,9 @@
+pub mod attachments;
 pub mod chat_area;
+pub mod export_menu;
+pub mod knowledge_picker;
+pub mod persona_picker;
 pub mod prompt_area;
+pub mod search_box;
+pub mod share_menu;
+pub mod template_picker;
\ No newline at end of file
@@ -0,0 +1,47 @@
+use crate::model::persona::Persona;
+use gloo_net::http::Request;
+use leptos::*;
+use uuid::Uuid;
+
+/// Renders the persona selector shown while a conversation has no messages yet.
+/// The choice is sent with the first prompt; afterwards the conversation keeps it.
+#[component]
+pub fn PersonaPicker(
+    persona_id: ReadSignal<Option<Uuid>>,
+    set_persona_id: WriteSignal<Option<Uuid>>,
+) -> impl IntoView {
+    let personas = create_local_resource(|| (), |_| list_personas());
+
+    view! {
+        <div class="flex gap-2 items-center text-sm">
+            <select
+                class="rounded-lg border dark:border-gray-600 bg-gray-100 dark:bg-gray-700 px-2 py-1.5"
+                on:change=move |ev| set_persona_id.set(Uuid::parse_str(&event_target_value(&ev)).ok().filter(|id| !id.is_nil()))
+            >
+                <Transition fallback=|| ()>
+                    {move || personas.get().map(|personas| personas.unwrap_or_default().into_iter().map(|persona| {
+                        let id = persona.id;
+                        view! {
+                            <option value=id.to_string() selected=move || persona_id.get().unwrap_or_default() == id>
+                                {persona.name}
+                            </option>
+                        }
+                    }).collect_view())}
+                </Transition>
+            </select>
+            <a href="/settings" class="text-gray-500 hover:underline">"Edit personas"</a>
+        </div>
+    }
+}
+
+pub async fn list_personas() -> Result<Vec<Persona>, String> {
+    let response = Request::get("/api/personas")
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    match response.ok() {
+        true => response.json().await.map_err(|e| e.to_string()),
+        false => Err(response.text().await.unwrap_or_else(|e| e.to_string())),
+    }
+}
@@ -1,36 +1,125 @@
-use leptos::*;
+use crate::app::commands::{self, CommandAction};
+use crate::app::components::attachments::{upload_attachment, AttachmentChip};
+use crate::app::components::template_picker::TemplatePicker;
+use crate::model::attachment::Attachment;
+use leptos::{html::Input, *};
+use wasm_bindgen::JsCast;
+
+/// A message ready to be sent, with the files attached to it.
+#[derive(Clone)]
+pub struct Prompt {
+    pub content: String,
+    pub attachments: Vec<Attachment>,
+}
 
 /// Renders the promt area of the chat page.
+///
+/// Input starting with `/` runs a slash command instead of being sent; `notice`
+/// shows command output and errors above the prompt. Files can be attached with
+/// the paperclip button or by dropping them on the prompt, and images by pasting
+/// them into it.
 #[component]
 pub fn PromptArea(
-    on_submit: Action<String, Result<(), ServerFnError>>,
+    on_submit: Action<Prompt, Result<(), ServerFnError>>,
+    on_command: Action<CommandAction, ()>,
+    notice: RwSignal<Option<String>>,
     is_loading: ReadSignal<bool>,
 ) -> impl IntoView {
     let (input, set_input) = create_signal(String::new());
+    let (highlighted, set_highlighted) = create_signal(0usize);
+    let suggestions = move || input.with(|input| commands::completions(input));
+    let attachments = create_rw_signal(Vec::<Attachment>::new());
+    let (uploading, set_uploading) = create_signal(0usize);
+    let (is_dragging, set_is_dragging) = create_signal(false);
+    let file_input_ref = create_node_ref::<Input>();
+
+    let can_send = move || (!input.get().is_empty() || attachments.with(|a| !a.is_empty())) && uploading.get() == 0 && !is_loading.get();
+
+    let attach = move |files: Option<web_sys::FileList>| {
+        let Some(files) = files else { return };
+        for file in (0..files.length()).filter_map(|i| files.get(i)) {
+            set_uploading.update(|n| *n += 1);
+            spawn_local(async move {
+                match upload_attachment(file).await {
+                    Ok(attachment) => attachments.update(|a| a.push(attachment)),
+                    Err(e) => notice.set(Some(e)),
+                }
+                set_uploading.update(|n| *n -= 1);
+            });
+        }
+    };
+
+    let submit = move || {
+        let text = input.get();
+        if !can_send() {
+            return;
+        }
+        match commands::parse(&text) {
+            Some(Ok(action)) => on_command.dispatch(action),
+            Some(Err(e)) => {
+                notice.set(Some(e));
+                return;
+            }
+            // `//text` sends `/text` as a normal message.
+            None => {
+                on_submit.dispatch(Prompt {
+                    content: text.strip_prefix('/').filter(|t| t.starts_with('/')).map_or(text.clone(), str::to_string),
+                    attachments: attachments.get(),
+                });
+                // Commands leave the attachments for the next message.
+                attachments.set(Vec::new());
+            }
+        }
+        set_input.set(String::new());
+    };
+
+    let complete = move |name: &str| {
+        set_input.set(format!("/{name} "));
+        set_highlighted.set(0);
+    };
 
     let handle_submit = move |ev: ev::SubmitEvent| {
         ev.prevent_default();
-        if !input.get().is_empty() && !is_loading.get() {
-            on_submit.dispatch(input.get());
-            set_input.set(String::new());
-        }
+        submit();
     };
 
     let handle_input_change = move |ev: ev::Event| {
         let value = event_target_value(&ev);
         set_input.set(value);
+        set_highlighted.set(0);
     };
 
-    // Handle keyup event to check if "Enter" key was pressed
-    let handle_keyup = move |ev: ev::KeyboardEvent| {
-        if ev.key() == "Enter" && !ev.shift_key() {
-            // Check if Enter is pressed without Shift key
-            ev.prevent_default(); // Prevent newline in the textarea
-            if !input.get().is_empty() && !is_loading.get() {
-                on_submit.dispatch(input.get());
-                set_input.set(String::new());
+    // Pasted images become attachments; pasted text goes into the textarea as usual.
+    let handle_paste = move |ev: ev::Event| {
+        let files = ev.dyn_ref::<web_sys::ClipboardEvent>()
+            .and_then(|ev| ev.clipboard_data())
+            .and_then(|data| data.files());
+        if files.as_ref().is_some_and(|files| files.length() > 0) {
+            ev.prevent_default();
+            attach(files);
+        }
+    };
+
+    // Enter without Shift sends the prompt; with Shift it inserts a newline as usual.
+    // Arrow keys move through the command suggestions and Tab accepts one.
+    let handle_keydown = move |ev: ev::KeyboardEvent| {
+        let suggestions = suggestions();
+        match ev.key().as_str() {
+            "Enter" if !ev.shift_key() => {
+                let typed = input.with(|input| input.trim_end().trim_start_matches('/').to_string());
+                // Enter on a partly typed command name completes it instead of running it.
+                match suggestions.get(highlighted.get()) {
+                    Some(command) if command.name != typed => complete(command.name),
+                    _ => submit(),
+                }
             }
+            _ if suggestions.is_empty() => return,
+            "ArrowDown" => set_highlighted.update(|i| *i = (*i + 1) % suggestions.len()),
+            "ArrowUp" => set_highlighted.update(|i| *i = (*i + suggestions.len() - 1) % suggestions.len()),
+            "Tab" => complete(suggestions[highlighted.get().min(suggestions.len() - 1)].name),
+            _ => return,
         }
+        ev.prevent_default();
     };
 
     view! {
@@ -38,32 +127,115 @@ pub fn PromptArea(
             <div class="bg-white dark:bg-gray-800">
                 <div class="max-w-3xl px-2.5 -mb-0.5 mx-auto inset-x-0">
                     <div class="bg-gradient-to-t from-white dark:from-gray-800 from-40% pb-2">
+                        {move || notice.get().map(|message| view! {
+                            <div class="mb-2 flex items-start gap-2 rounded-lg bg-gray-100 dark:bg-gray-700 px-3 py-2 text-sm">
+                                <p class="flex-grow whitespace-pre-wrap">{message}</p>
+                                <button class="text-gray-500 hover:text-gray-700 dark:hover:text-gray-300" on:click=move |_| notice.set(None)>
+                                    "×"
+                                </button>
+                            </div>
+                        })}
+                        <Show when=move || !suggestions().is_empty()>
+                            <ul class="mb-2 rounded-lg border dark:border-gray-600 bg-white dark:bg-gray-800 shadow-lg py-1 text-sm">
+                                {move || suggestions().into_iter().enumerate().map(|(i, command)| view! {
+                                    <li
+                                        class="flex gap-3 px-3 py-1 cursor-pointer"
+                                        class=("bg-gray-100", move || highlighted.get() == i)
+                                        class=("dark:bg-gray-700", move || highlighted.get() == i)
+                                        // mousedown keeps the focus in the textarea
+                                        on:mousedown=move |ev| {
+                                            ev.prevent_default();
+                                            complete(command.name);
+                                        }
+                                    >
+                                        <span class="font-mono">{command.usage}</span>
+                                        <span class="text-gray-500">{command.description}</span>
+                                    </li>
+                                }).collect_view()}
+                            </ul>
+                        </Show>
                         <form
                             class="flex flex-col relative w-full rounded-xl border dark:border-gray-600 bg-white dark:bg-gray-800 dark:text-gray-100"
+                            class=("ring-2", is_dragging)
+                            class=("ring-indigo-500", is_dragging)
                             on:submit=handle_submit
+                            on:dragover=move |ev: ev::DragEvent| {
+                                ev.prevent_default();
+                                set_is_dragging.set(true);
+                            }
+                            on:dragleave=move |_| set_is_dragging.set(false)
+                            on:drop=move |ev: ev::DragEvent| {
+                                ev.prevent_default();
+                                set_is_dragging.set(false);
+                                attach(ev.data_transfer().and_then(|data| data.files()));
+                            }
                         >
+                            <Show when=move || { attachments.with(|a| !a.is_empty()) || uploading.get() > 0 }>
+                                <div class="flex flex-wrap items-start gap-1 px-3 pt-2">
+                                    {move || attachments.get().into_iter().map(|attachment| {
+                                        let id = attachment.id;
+                                        view! {
+                                            <AttachmentChip
+                                                attachment
+                                                on_remove=move |_| attachments.update(|a| a.retain(|a| a.id != id))
+                                            />
+                                        }
+                                    }).collect_view()}
+                                    {move || (uploading.get() > 0).then(|| view! {
+                                        <span class="text-xs text-gray-500 py-0.5">"Uploading…"</span>
+                                    })}
+                                </div>
+                            </Show>
                             <div class="flex">
                                 <textarea
                                     class="dark:bg-gray-800 dark:text-gray-100 outline-none w-full py-3 px-2 pl-4 rounded-xl resize-none h-[48px]"
                                     rows=3
                                     prop:value=input
-                                    placeholder="Send a message"
+                                    placeholder="Send a message, or / for commands"
                                     on:input=handle_input_change
                                     prop:disabled=is_loading
-                                    on:keyup=handle_keyup
+                                    on:keydown=handle_keydown
+                                    on:paste=handle_paste
                                 />
                                 <div class="self-end mb-2 flex space-x-0.5 mr-2">
+                                    <input
+                                        type="file"
+                                        multiple
+                                        class="hidden"
+                                        accept=".txt,.md,.markdown,.pdf,.csv,.tsv,.json,.yaml,.yml,.toml,.xml,.html,.css,.js,.ts,.py,.rs,.go,.java,.c,.h,.cpp,.rb,.sh,.sql,text/*,application/pdf,image/png,image/jpeg,image/gif,image/webp"
+                                        node_ref=file_input_ref
+                                        on:change=move |_| {
+                                            if let Some(input) = file_input_ref.get() {
+                                                attach(input.files());
+                                                input.set_value("");
+                                            }
+                                        }
+                                    />
+                                    <button
+                                        type="button"
+                                        title="Attach files"
+                                        class="rounded-lg p-1 w-7 h-7 self-center text-gray-500 hover:bg-gray-100 dark:hover:bg-gray-700"
+                                        on:click=move |_| if let Some(input) = file_input_ref.get() { input.click() }
+                                    >
+                                        "📎"
+                                    </button>
+                                    <TemplatePicker on_insert=move |text: String| set_input.update(|input| {
+                                        if !input.is_empty() {
+                                            input.push('\n');
+                                        }
+                                        input.push_str(&text);
+                                    })/>
                                     <button
                                         class=move || {
                                             let base_classes = "transition rounded-lg p-1 mr-0.5 w-7 h-7 self-center";
-                                            if !input.get().is_empty() {
+                                            if can_send() {
                                                 format!("{} bg-black text-white hover:bg-gray-900 dark:bg-white dark:text-black dark:hover:bg-gray-100", base_classes)
                                             } else {
                                                 format!("{} text-white bg-gray-100 dark:text-gray-800 dark:bg-gray-600 disabled", base_classes)
                                             }
                                         }
                                         type="submit"
-                                        prop:disabled=move || input.get().is_empty() || is_loading.get()
+                                        prop:disabled=move || !can_send()
                                     >
                                         <svg
                                             xmlns="http://www.w3.org/2000/svg"
@@ -0,0 +1,97 @@
+use crate::model::search::{SearchHit, SearchMode};
+use gloo_net::http::Request;
+use leptos::*;
+
+/// Renders the search box of the chat page: searches every stored conversation
+/// and links each hit to the message it matched.
+#[component]
+pub fn SearchBox() -> impl IntoView {
+    let (query, set_query) = create_signal(String::new());
+    let (mode, set_mode) = create_signal(SearchMode::FullText);
+
+    let hits = create_local_resource(
+        move || (query.get(), mode.get()),
+        |(query, mode)| async move {
+            match query.trim().is_empty() {
+                true => Ok(Vec::new()),
+                false => search(&query, mode).await,
+            }
+        },
+    );
+
+    let on_submit = move |ev: ev::SubmitEvent| ev.prevent_default();
+
+    view! {
+        <details class="relative text-sm">
+            <summary class="cursor-pointer list-none rounded-lg px-3 py-1.5 bg-gray-100 dark:bg-gray-700 hover:bg-gray-200 dark:hover:bg-gray-600">
+                "Search"
+            </summary>
+            <div class="absolute left-0 mt-1 w-96 rounded-lg border dark:border-gray-600 bg-white dark:bg-gray-800 shadow-lg p-3 flex flex-col gap-2">
+                <form class="flex gap-2" on:submit=on_submit>
+                    <input
+                        type="search"
+                        placeholder="Search conversations"
+                        class="flex-grow rounded-md border dark:border-gray-600 dark:bg-gray-800 px-2 py-1 outline-none"
+                        on:change=move |ev| set_query.set(event_target_value(&ev))
+                    />
+                    <select
+                        class="rounded-md border dark:border-gray-600 dark:bg-gray-800 px-2 py-1"
+                        on:change=move |ev| set_mode.set(match event_target_value(&ev).as_str() {
+                            "semantic" => SearchMode::Semantic,
+                            _ => SearchMode::FullText,
+                        })
+                    >
+                        <option value="fulltext">"Text"</option>
+                        <option value="semantic">"Semantic"</option>
+                    </select>
+                </form>
+                <Transition fallback=|| ()>
+                    {move || hits.get().map(|hits| match hits {
+                        Ok(hits) if hits.is_empty() && !query.with(|q| q.trim().is_empty()) => {
+                            view! { <p class="text-xs text-gray-500">"No matches."</p> }.into_view()
+                        }
+                        Ok(hits) => view! {
+                            <ul class="max-h-96 overflow-y-auto flex flex-col">
+                                {hits.into_iter().map(|hit| view! { <SearchResult hit/> }).collect_view()}
+                            </ul>
+                        }.into_view(),
+                        Err(e) => view! { <p class="text-xs text-red-500">{e}</p> }.into_view(),
+                    })}
+                </Transition>
+            </div>
+        </details>
+    }
+}
+
+#[component]
+fn SearchResult(hit: SearchHit) -> impl IntoView {
+    let href = format!("/c/{}#{}", hit.conversation_id, hit.message_id);
+    let role = hit.role.label();
+
+    view! {
+        <li class="border-t dark:border-gray-600 first:border-t-0">
+            <a href=href class="block px-1 py-2 hover:bg-gray-100 dark:hover:bg-gray-700 rounded-md">
+                <p class="text-xs text-gray-500">{hit.conversation_name} " · " {role}</p>
+                // The snippet is escaped server-side; only the `<mark>` highlights are markup.
+                <p class="text-sm [&_mark]:bg-yellow-200 dark:[&_mark]:bg-yellow-700 dark:[&_mark]:text-white" inner_html=hit.snippet></p>
+            </a>
+        </li>
+    }
+}
+
+async fn search(query: &str, mode: SearchMode) -> Result<Vec<SearchHit>, String> {
+    let mode = match mode {
+        SearchMode::FullText => "fulltext",
+        SearchMode::Semantic => "semantic",
+    };
+    let response = Request::get("/api/search")
+        .query([("q", query), ("mode", mode)])
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    match response.ok() {
+        true => response.json().await.map_err(|e| e.to_string()),
+        false => Err(response.text().await.unwrap_or_else(|e| e.to_string())),
+    }
+}
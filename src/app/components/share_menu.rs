@@ -0,0 +1,178 @@
+use crate::model::conversation::Conversation;
+use crate::model::share::{ShareRequest, ShareSummary};
+use crate::model::template::CLIENT_ID_HEADER;
+use super::template_picker::client_id;
+use chrono::DateTime;
+use gloo_net::http::Request;
+use leptos::{html::Input, *};
+use uuid::Uuid;
+
+const EXPIRY_OPTIONS: [(&str, &str); 4] = [("Never expires", ""), ("Expires in 1 day", "24"), ("Expires in 7 days", "168"), ("Expires in 30 days", "720")];
+
+/// Renders the share menu of the chat page: creates, lists and revokes the
+/// read-only links of the current conversation.
+#[component]
+pub fn ShareMenu(conversation: ReadSignal<Conversation>) -> impl IntoView {
+    let (refresh, set_refresh) = create_signal(0);
+    let (expires_in_hours, set_expires_in_hours) = create_signal(None::<u32>);
+    let (error, set_error) = create_signal(None::<String>);
+    let password_ref = create_node_ref::<Input>();
+
+    let shares = create_local_resource(
+        move || (conversation.with(|c| c.id), refresh.get()),
+        |(id, _)| list_shares(id),
+    );
+
+    let create_share = create_action(move |request: &ShareRequest| {
+        let id = conversation.get_untracked().id;
+        let request = request.clone();
+        async move {
+            match post_share(id, &request).await {
+                Ok(()) => set_error.set(None),
+                Err(e) => set_error.set(Some(e)),
+            }
+            set_refresh.update(|n| *n += 1);
+        }
+    });
+
+    let revoke_share = create_action(move |token: &String| {
+        let token = token.clone();
+        async move {
+            if let Err(e) = revoke_share(&token).await {
+                set_error.set(Some(e));
+            }
+            set_refresh.update(|n| *n += 1);
+        }
+    });
+
+    let on_submit = move |ev: ev::SubmitEvent| {
+        ev.prevent_default();
+        let password = password_ref.get().map(|input| {
+            let value = input.value();
+            input.set_value("");
+            value
+        });
+        create_share.dispatch(ShareRequest {
+            expires_in_hours: expires_in_hours.get_untracked(),
+            password: password.filter(|p| !p.is_empty()),
+        });
+    };
+
+    view! {
+        <Show when=move || !conversation.with(|c| c.messages.is_empty())>
+            <details class="relative text-sm">
+                <summary class="cursor-pointer list-none rounded-lg px-3 py-1.5 bg-gray-100 dark:bg-gray-700 hover:bg-gray-200 dark:hover:bg-gray-600">
+                    "Share"
+                </summary>
+                <div class="absolute right-0 mt-1 w-80 rounded-lg border dark:border-gray-600 bg-white dark:bg-gray-800 shadow-lg p-3 flex flex-col gap-3">
+                    <form class="flex flex-col gap-2" on:submit=on_submit>
+                        <select
+                            class="rounded-md border dark:border-gray-600 dark:bg-gray-800 px-2 py-1"
+                            on:change=move |ev| set_expires_in_hours.set(event_target_value(&ev).parse().ok())
+                        >
+                            {EXPIRY_OPTIONS.map(|(label, value)| view! { <option value=value>{label}</option> })}
+                        </select>
+                        <input
+                            type="password"
+                            placeholder="Optional password"
+                            node_ref=password_ref
+                            class="rounded-md border dark:border-gray-600 dark:bg-gray-800 px-2 py-1 outline-none"
+                        />
+                        <button
+                            type="submit"
+                            class="rounded-md px-3 py-1.5 bg-black text-white hover:bg-gray-900 dark:bg-white dark:text-black dark:hover:bg-gray-100"
+                            prop:disabled=move || create_share.pending().get()
+                        >
+                            "Create read-only link"
+                        </button>
+                    </form>
+                    {move || error.get().map(|e| view! { <p class="text-xs text-red-500">{e}</p> })}
+                    <Transition fallback=|| ()>
+                        {move || shares.get().map(|shares| match shares {
+                            Ok(shares) => shares.into_iter().map(|share| view! {
+                                <ShareLink share on_revoke=move |token| revoke_share.dispatch(token)/>
+                            }).collect_view(),
+                            Err(e) => view! { <p class="text-xs text-red-500">{e}</p> }.into_view(),
+                        })}
+                    </Transition>
+                </div>
+            </details>
+        </Show>
+    }
+}
+
+#[component]
+fn ShareLink<F>(share: ShareSummary, on_revoke: F) -> impl IntoView
+where
+    F: Fn(String) + 'static,
+{
+    let origin = web_sys::window().and_then(|w| w.location().origin().ok()).unwrap_or_default();
+    let url = format!("{origin}/s/{}", share.token);
+    let mut details = Vec::new();
+    if share.password_protected {
+        details.push("password".to_string());
+    }
+    if let Some(expires_at) = share.expires_at.and_then(|t| DateTime::from_timestamp(t, 0)) {
+        details.push(format!("expires {}", expires_at.format("%Y-%m-%d %H:%M UTC")));
+    }
+    let token = share.token.clone();
+
+    view! {
+        <div class="flex flex-col gap-1 border-t dark:border-gray-600 pt-2">
+            <input
+                readonly
+                class="w-full rounded-md bg-gray-100 dark:bg-gray-700 px-2 py-1 text-xs"
+                prop:value=url
+                on:focus=|ev| {
+                    use wasm_bindgen::JsCast;
+                    if let Some(input) = ev.target().and_then(|t| t.dyn_into::<web_sys::HtmlInputElement>().ok()) {
+                        input.select();
+                    }
+                }
+            />
+            <div class="flex justify-between text-xs text-gray-500">
+                <span>{details.join(" · ")}</span>
+                <button class="text-red-500 hover:underline" on:click=move |_| on_revoke(token.clone())>
+                    "Revoke"
+                </button>
+            </div>
+        </div>
+    }
+}
+
+async fn list_shares(id: Uuid) -> Result<Vec<ShareSummary>, String> {
+    let response = Request::get(&format!("/api/conversations/{id}/shares"))
+        .header(CLIENT_ID_HEADER, &client_id())
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    response.json().await.map_err(|e| e.to_string())
+}
+
+async fn post_share(id: Uuid, request: &ShareRequest) -> Result<(), String> {
+    let response = Request::post(&format!("/api/conversations/{id}/shares"))
+        .header(CLIENT_ID_HEADER, &client_id())
+        .json(request)
+        .map_err(|e| e.to_string())?
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    match response.ok() {
+        true => Ok(()),
+        false => Err(response.text().await.unwrap_or_else(|e| e.to_string())),
+    }
+}
+
+async fn revoke_share(token: &str) -> Result<(), String> {
+    let response = Request::delete(&format!("/api/shares/{token}"))
+        .header(CLIENT_ID_HEADER, &client_id())
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    match response.ok() {
+        true => Ok(()),
+        false => Err(response.text().await.unwrap_or_else(|e| e.to_string())),
+    }
+}
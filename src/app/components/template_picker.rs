@@ -0,0 +1,181 @@
+use crate::model::template::{PromptTemplate, CLIENT_ID_HEADER};
+use gloo_net::http::Request;
+use leptos::{html::Details, *};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+const CLIENT_ID_KEY: &str = "rustic-ai.client-id";
+
+/// Renders the template menu of the prompt area. Choosing a template with
+/// variables asks for their values first; the rendered text goes to `on_insert`.
+#[component]
+pub fn TemplatePicker<F>(on_insert: F) -> impl IntoView
+where
+    F: Fn(String) + Copy + 'static,
+{
+    let details_ref = create_node_ref::<Details>();
+    let (opened, set_opened) = create_signal(0);
+    let (selected, set_selected) = create_signal(None::<PromptTemplate>);
+    let templates = create_local_resource(move || opened.get(), |_| list_templates());
+
+    let insert = move |text: String| {
+        on_insert(text);
+        set_selected.set(None);
+        if let Some(details) = details_ref.get() {
+            details.set_open(false);
+        }
+    };
+
+    let choose = move |template: PromptTemplate| match template.variables().is_empty() {
+        true => insert(template.body),
+        false => set_selected.set(Some(template)),
+    };
+
+    view! {
+        <details
+            class="relative self-center"
+            node_ref=details_ref
+            on:toggle=move |_| set_opened.update(|n| *n += 1)
+        >
+            <summary class="cursor-pointer list-none rounded-lg px-2 py-1 text-xs text-gray-500 hover:bg-gray-100 dark:hover:bg-gray-700">
+                "Templates"
+            </summary>
+            <div class="absolute bottom-full right-0 mb-2 w-80 rounded-lg border dark:border-gray-600 bg-white dark:bg-gray-800 shadow-lg p-2 text-sm">
+                {move || match selected.get() {
+                    Some(template) => view! {
+                        <VariableForm template on_insert=insert on_cancel=move || set_selected.set(None)/>
+                    }.into_view(),
+                    None => view! {
+                        <Transition fallback=|| ()>
+                            {move || templates.get().map(|templates| match templates {
+                                Ok(templates) if templates.is_empty() => view! {
+                                    <p class="px-2 py-1 text-gray-500">"No templates yet."</p>
+                                }.into_view(),
+                                Ok(templates) => templates.into_iter().map(|template| {
+                                    let name = template.name.clone();
+                                    let shared = template.shared;
+                                    view! {
+                                        <button
+                                            type="button"
+                                            class="w-full flex justify-between text-left rounded-md px-2 py-1 hover:bg-gray-100 dark:hover:bg-gray-700"
+                                            on:click=move |_| choose(template.clone())
+                                        >
+                                            <span>{name}</span>
+                                            {shared.then(|| view! { <span class="text-xs text-gray-500">"shared"</span> })}
+                                        </button>
+                                    }
+                                }).collect_view(),
+                                Err(e) => view! { <p class="px-2 py-1 text-red-500">{e}</p> }.into_view(),
+                            })}
+                        </Transition>
+                        <a href="/settings#templates" class="block px-2 pt-2 mt-1 border-t dark:border-gray-600 text-xs text-gray-500 hover:underline">
+                            "Manage templates"
+                        </a>
+                    }.into_view(),
+                }}
+            </div>
+        </details>
+    }
+}
+
+#[component]
+fn VariableForm<I, C>(template: PromptTemplate, on_insert: I, on_cancel: C) -> impl IntoView
+where
+    I: Fn(String) + 'static,
+    C: Fn() + 'static,
+{
+    let values = create_rw_signal(HashMap::<String, String>::new());
+    let name = template.name.clone();
+    let variables = template.variables();
+
+    let on_submit = move |ev: ev::SubmitEvent| {
+        ev.prevent_default();
+        on_insert(values.with_untracked(|values| template.render(values)));
+    };
+
+    view! {
+        <form class="flex flex-col gap-2 p-1" on:submit=on_submit>
+            <p class="font-medium">{name}</p>
+            {variables.into_iter().map(|variable| {
+                let key = variable.clone();
+                view! {
+                    <label class="flex flex-col gap-1 text-xs text-gray-500">
+                        {variable}
+                        <textarea
+                            rows=1
+                            class="rounded-md border dark:border-gray-600 dark:bg-gray-800 px-2 py-1 text-sm text-gray-700 dark:text-gray-100 outline-none"
+                            on:input=move |ev| {
+                                let value = event_target_value(&ev);
+                                values.update(|values| { values.insert(key.clone(), value); });
+                            }
+                        />
+                    </label>
+                }
+            }).collect_view()}
+            <div class="flex gap-2 justify-end">
+                <button type="button" class="rounded-md px-3 py-1 hover:bg-gray-100 dark:hover:bg-gray-700" on:click=move |_| on_cancel()>
+                    "Back"
+                </button>
+                <button type="submit" class="rounded-md px-3 py-1 bg-black text-white hover:bg-gray-900 dark:bg-white dark:text-black dark:hover:bg-gray-100">
+                    "Insert"
+                </button>
+            </div>
+        </form>
+    }
+}
+
+/// Random id of this browser, created on first use and kept in local storage.
+/// The server uses it to tell whose private templates are whose.
+pub fn client_id() -> String {
+    let storage = window().local_storage().ok().flatten();
+    if let Some(id) = storage.as_ref().and_then(|storage| storage.get_item(CLIENT_ID_KEY).ok().flatten()) {
+        return id;
+    }
+
+    let id = Uuid::new_v4().simple().to_string();
+    if let Some(storage) = storage {
+        let _ = storage.set_item(CLIENT_ID_KEY, &id);
+    }
+    id
+}
+
+pub async fn list_templates() -> Result<Vec<PromptTemplate>, String> {
+    let response = Request::get("/api/templates")
+        .header(CLIENT_ID_HEADER, &client_id())
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    match response.ok() {
+        true => response.json().await.map_err(|e| e.to_string()),
+        false => Err(response.text().await.unwrap_or_else(|e| e.to_string())),
+    }
+}
+
+pub async fn save_template(template: &PromptTemplate) -> Result<PromptTemplate, String> {
+    let response = Request::put(&format!("/api/templates/{}", template.id))
+        .header(CLIENT_ID_HEADER, &client_id())
+        .json(template)
+        .map_err(|e| e.to_string())?
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    match response.ok() {
+        true => response.json().await.map_err(|e| e.to_string()),
+        false => Err(response.text().await.unwrap_or_else(|e| e.to_string())),
+    }
+}
+
+pub async fn delete_template(id: Uuid) -> Result<(), String> {
+    let response = Request::delete(&format!("/api/templates/{id}"))
+        .header(CLIENT_ID_HEADER, &client_id())
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    match response.ok() {
+        true => Ok(()),
+        false => Err(response.text().await.unwrap_or_else(|e| e.to_string())),
+    }
+}
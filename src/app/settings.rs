@@ -0,0 +1,375 @@
+use crate::app::components::persona_picker::list_personas;
+use crate::model::persona::{ExampleTurn, Persona};
+use crate::model::tool::ToolInfo;
+use gloo_net::http::Request;
+use leptos::*;
+use leptos_meta::*;
+use serde::Deserialize;
+use std::str::FromStr;
+use uuid::Uuid;
+
+mod knowledge;
+mod templates;
+use knowledge::KnowledgeSettings;
+use templates::TemplateSettings;
+
+const INPUT_CLASS: &str = "rounded-md border dark:border-gray-600 dark:bg-gray-800 px-2 py-1 outline-none";
+const BUTTON_CLASS: &str = "rounded-md px-3 py-1.5 bg-black text-white hover:bg-gray-900 dark:bg-white dark:text-black dark:hover:bg-gray-100";
+
+/// Renders the settings page at `/settings`, where personas, prompt templates and
+/// knowledge bases are managed.
+#[component]
+pub fn SettingsPage() -> impl IntoView {
+    let (refresh, set_refresh) = create_signal(0);
+    let personas = create_local_resource(move || refresh.get(), |_| list_personas());
+    let models = create_local_resource(|| (), |_| list_models());
+    let tools = create_local_resource(|| (), |_| list_tools());
+    let draft = create_rw_signal(None::<Persona>);
+    let (error, set_error) = create_signal(None::<String>);
+
+    let save = create_action(move |persona: &Persona| {
+        let persona = persona.clone();
+        async move {
+            match save_persona(&persona).await {
+                Ok(saved) => {
+                    draft.set(Some(saved));
+                    set_error.set(None);
+                }
+                Err(e) => set_error.set(Some(e)),
+            }
+            set_refresh.update(|n| *n += 1);
+        }
+    });
+
+    let delete = create_action(move |id: &Uuid| {
+        let id = *id;
+        async move {
+            match delete_persona(id).await {
+                Ok(()) => {
+                    draft.set(None);
+                    set_error.set(None);
+                }
+                Err(e) => set_error.set(Some(e)),
+            }
+            set_refresh.update(|n| *n += 1);
+        }
+    });
+
+    view! {
+        <Title text="Settings"/>
+        <div class="py-10 px-5 flex flex-col gap-6">
+            <div class="flex justify-between items-baseline">
+                <h1 class="text-2xl font-semibold">"Settings"</h1>
+                <a href="/" class="text-sm text-gray-500 hover:underline">"Back to chat"</a>
+            </div>
+            <h2 id="personas" class="text-lg font-semibold">"Personas"</h2>
+            <div class="flex gap-6">
+                <ul class="w-48 shrink-0 flex flex-col gap-1 text-sm">
+                    <Transition fallback=|| ()>
+                        {move || personas.get().map(|personas| match personas {
+                            Ok(personas) => personas.into_iter().map(|persona| {
+                                let id = persona.id;
+                                let name = persona.name.clone();
+                                let is_selected = move || draft.with(|d| d.as_ref().is_some_and(|d| d.id == id));
+                                view! {
+                                    <li>
+                                        <button
+                                            class="w-full text-left rounded-md px-2 py-1 hover:bg-gray-100 dark:hover:bg-gray-700"
+                                            class=("bg-gray-100", is_selected)
+                                            class=("dark:bg-gray-700", is_selected)
+                                            on:click=move |_| draft.set(Some(persona.clone()))
+                                        >
+                                            {name}
+                                        </button>
+                                    </li>
+                                }
+                            }).collect_view(),
+                            Err(e) => view! { <li class="text-red-500">{e}</li> }.into_view(),
+                        })}
+                    </Transition>
+                    <li>
+                        <button
+                            class="w-full text-left rounded-md px-2 py-1 text-indigo-500 hover:bg-gray-100 dark:hover:bg-gray-700"
+                            on:click=move |_| draft.set(Some(Persona::new("New persona".to_string())))
+                        >
+                            "+ New persona"
+                        </button>
+                    </li>
+                </ul>
+                <div class="flex-grow">
+                    {move || error.get().map(|e| view! { <p class="mb-2 text-sm text-red-500">{e}</p> })}
+                    <Show
+                        when=move || draft.with(Option::is_some)
+                        fallback=|| view! { <p class="text-sm text-gray-500">"Select a persona to edit it."</p> }
+                    >
+                        <PersonaEditor
+                            draft
+                            is_new=Signal::derive(move || {
+                                let id = draft.with(|d| d.as_ref().map(|d| d.id));
+                                !personas.with(|p| p.as_ref().and_then(|p| p.as_ref().ok()).is_some_and(|p| p.iter().any(|p| Some(p.id) == id)))
+                            })
+                            models=Signal::derive(move || models.get().and_then(Result::ok).unwrap_or_default())
+                            tools=Signal::derive(move || tools.get().and_then(Result::ok).unwrap_or_default())
+                            on_save=move |persona| save.dispatch(persona)
+                            on_delete=move |id| delete.dispatch(id)
+                        />
+                    </Show>
+                </div>
+            </div>
+            <h2 id="templates" class="text-lg font-semibold">"Prompt templates"</h2>
+            <TemplateSettings/>
+            <h2 id="knowledge" class="text-lg font-semibold">"Knowledge bases"</h2>
+            <KnowledgeSettings/>
+        </div>
+    }
+}
+
+#[component]
+fn PersonaEditor<S, D>(
+    draft: RwSignal<Option<Persona>>,
+    is_new: Signal<bool>,
+    models: Signal<Vec<String>>,
+    tools: Signal<Vec<ToolInfo>>,
+    on_save: S,
+    on_delete: D,
+) -> impl IntoView
+where
+    S: Fn(Persona) + 'static,
+    D: Fn(Uuid) + 'static,
+{
+    let field = move |read: fn(&Persona) -> String| move || draft.with(|d| d.as_ref().map(read).unwrap_or_default());
+    let edit = move |update: fn(&mut Persona, String)| {
+        move |ev: ev::Event| {
+            let value = event_target_value(&ev);
+            draft.update(|d| if let Some(d) = d { update(d, value) });
+        }
+    };
+    let is_default = move || draft.with(|d| d.as_ref().is_some_and(|d| d.id.is_nil()));
+    let on_delete = store_value(on_delete);
+    let example_count = move || draft.with(|d| d.as_ref().map_or(0, |d| d.examples.len()));
+
+    let on_submit = move |ev: ev::SubmitEvent| {
+        ev.prevent_default();
+        if let Some(persona) = draft.get_untracked() {
+            on_save(persona);
+        }
+    };
+
+    view! {
+        <form class="flex flex-col gap-3 text-sm" on:submit=on_submit>
+            <label class="flex flex-col gap-1">
+                "Name"
+                <input class=INPUT_CLASS prop:value=field(|p| p.name.clone()) on:input=edit(|p, v| p.name = v)/>
+            </label>
+            <label class="flex flex-col gap-1">
+                "Model"
+                <input
+                    class=INPUT_CLASS
+                    list="persona-models"
+                    placeholder="Server default"
+                    prop:value=field(|p| p.model.clone().unwrap_or_default())
+                    on:input=edit(|p, v| p.model = Some(v).filter(|v| !v.is_empty()))
+                />
+                <datalist id="persona-models">
+                    {move || models.get().into_iter().map(|model| view! { <option value=model/> }).collect_view()}
+                </datalist>
+            </label>
+            <label class="flex flex-col gap-1">
+                "System prompt"
+                <textarea
+                    class=INPUT_CLASS
+                    rows=5
+                    prop:value=field(|p| p.system_prompt.clone())
+                    on:input=edit(|p, v| p.system_prompt = v)
+                />
+            </label>
+
+            <fieldset class="flex flex-col gap-2">
+                <legend class="mb-1">"Example turns"</legend>
+                {move || (0..example_count()).map(|i| view! { <ExampleEditor draft index=i/> }).collect_view()}
+                <button
+                    type="button"
+                    class="self-start text-indigo-500 hover:underline"
+                    on:click=move |_| draft.update(|d| if let Some(d) = d { d.examples.push(ExampleTurn::default()) })
+                >
+                    "+ Add example"
+                </button>
+            </fieldset>
+
+            <fieldset class="grid grid-cols-3 gap-2">
+                <legend class="mb-1">"Generation options"</legend>
+                <OptionInput draft label="Temperature" step="0.05"
+                    read=|p| p.options.temperature.map(|v| v.to_string())
+                    write=|p, v| p.options.temperature = parse(&v)/>
+                <OptionInput draft label="Top P" step="0.05"
+                    read=|p| p.options.top_p.map(|v| v.to_string())
+                    write=|p, v| p.options.top_p = parse(&v)/>
+                <OptionInput draft label="Top K" step="1"
+                    read=|p| p.options.top_k.map(|v| v.to_string())
+                    write=|p, v| p.options.top_k = parse(&v)/>
+                <OptionInput draft label="Context length" step="1"
+                    read=|p| p.options.num_ctx.map(|v| v.to_string())
+                    write=|p, v| p.options.num_ctx = parse(&v)/>
+                <OptionInput draft label="Max tokens" step="1"
+                    read=|p| p.options.num_predict.map(|v| v.to_string())
+                    write=|p, v| p.options.num_predict = parse(&v)/>
+                <OptionInput draft label="Seed" step="1"
+                    read=|p| p.options.seed.map(|v| v.to_string())
+                    write=|p, v| p.options.seed = parse(&v)/>
+            </fieldset>
+
+            <fieldset class="flex flex-col gap-1">
+                <legend class="mb-1">"Tools"</legend>
+                {move || tools.get().into_iter().map(|tool| {
+                    let name = tool.name.clone();
+                    let is_enabled = {
+                        let name = name.clone();
+                        move || draft.with(|d| d.as_ref().is_some_and(|d| d.tools.contains(&name)))
+                    };
+                    view! {
+                        <label class="flex gap-2 items-baseline">
+                            <input
+                                type="checkbox"
+                                prop:checked=is_enabled
+                                on:change=move |ev| {
+                                    let checked = event_target_checked(&ev);
+                                    draft.update(|d| if let Some(d) = d {
+                                        d.tools.retain(|t| *t != name);
+                                        if checked {
+                                            d.tools.push(name.clone());
+                                        }
+                                    });
+                                }
+                            />
+                            <span>
+                                <span class="font-mono">{tool.name}</span>
+                                <span class="text-gray-500">" – " {tool.description}</span>
+                            </span>
+                        </label>
+                    }
+                }).collect_view()}
+            </fieldset>
+
+            <div class="flex gap-2">
+                <button type="submit" class=BUTTON_CLASS>"Save"</button>
+                <Show when=move || !is_new.get()>
+                    <button
+                        type="button"
+                        class="rounded-md px-3 py-1.5 text-red-500 hover:bg-gray-100 dark:hover:bg-gray-700"
+                        on:click=move |_| {
+                            if let Some(id) = draft.with_untracked(|d| d.as_ref().map(|d| d.id)) {
+                                on_delete.with_value(|on_delete| on_delete(id));
+                            }
+                        }
+                    >
+                        {move || if is_default() { "Reset to default" } else { "Delete" }}
+                    </button>
+                </Show>
+            </div>
+        </form>
+    }
+}
+
+#[component]
+fn ExampleEditor(draft: RwSignal<Option<Persona>>, index: usize) -> impl IntoView {
+    let read = move |assistant: bool| move || draft.with(|d| {
+        d.as_ref().and_then(|d| d.examples.get(index)).map(|e| if assistant { e.assistant.clone() } else { e.user.clone() }).unwrap_or_default()
+    });
+    let write = move |assistant: bool| move |ev: ev::Event| {
+        let value = event_target_value(&ev);
+        draft.update(|d| if let Some(example) = d.as_mut().and_then(|d| d.examples.get_mut(index)) {
+            if assistant { example.assistant = value } else { example.user = value }
+        });
+    };
+
+    view! {
+        <div class="flex gap-2 items-start">
+            <textarea class=format!("{INPUT_CLASS} flex-1") rows=2 placeholder="User" prop:value=read(false) on:input=write(false)/>
+            <textarea class=format!("{INPUT_CLASS} flex-1") rows=2 placeholder="Assistant" prop:value=read(true) on:input=write(true)/>
+            <button
+                type="button"
+                class="px-2 text-gray-500 hover:text-red-500"
+                on:click=move |_| draft.update(|d| if let Some(d) = d { d.examples.remove(index); })
+            >
+                "×"
+            </button>
+        </div>
+    }
+}
+
+#[component]
+fn OptionInput(
+    draft: RwSignal<Option<Persona>>,
+    label: &'static str,
+    step: &'static str,
+    read: fn(&Persona) -> Option<String>,
+    write: fn(&mut Persona, String),
+) -> impl IntoView {
+    view! {
+        <label class="flex flex-col gap-1">
+            {label}
+            <input
+                type="number"
+                step=step
+                placeholder="Default"
+                class=INPUT_CLASS
+                prop:value=move || draft.with(|d| d.as_ref().and_then(read).unwrap_or_default())
+                on:change=move |ev| {
+                    let value = event_target_value(&ev);
+                    draft.update(|d| if let Some(d) = d { write(d, value) });
+                }
+            />
+        </label>
+    }
+}
+
+fn parse<T: FromStr>(value: &str) -> Option<T> {
+    value.trim().parse().ok()
+}
+
+async fn save_persona(persona: &Persona) -> Result<Persona, String> {
+    let response = Request::put(&format!("/api/personas/{}", persona.id))
+        .json(persona)
+        .map_err(|e| e.to_string())?
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    match response.ok() {
+        true => response.json().await.map_err(|e| e.to_string()),
+        false => Err(response.text().await.unwrap_or_else(|e| e.to_string())),
+    }
+}
+
+async fn delete_persona(id: Uuid) -> Result<(), String> {
+    let response = Request::delete(&format!("/api/personas/{id}"))
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    match response.ok() {
+        true => Ok(()),
+        false => Err(response.text().await.unwrap_or_else(|e| e.to_string())),
+    }
+}
+
+#[derive(Deserialize)]
+struct ModelList {
+    data: Vec<ModelEntry>,
+}
+
+#[derive(Deserialize)]
+struct ModelEntry {
+    id: String,
+}
+
+async fn list_models() -> Result<Vec<String>, String> {
+    let response = Request::get("/v1/models").send().await.map_err(|e| e.to_string())?;
+    let models: ModelList = response.json().await.map_err(|e| e.to_string())?;
+    Ok(models.data.into_iter().map(|model| model.id).collect())
+}
+
+async fn list_tools() -> Result<Vec<ToolInfo>, String> {
+    let response = Request::get("/api/tools").send().await.map_err(|e| e.to_string())?;
+    response.json().await.map_err(|e| e.to_string())
+}
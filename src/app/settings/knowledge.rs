@@ -0,0 +1,284 @@
+use super::{BUTTON_CLASS, INPUT_CLASS};
+use crate::app::components::knowledge_picker::{
+    create_knowledge_base, delete_document, delete_knowledge_base, list_knowledge_bases, sync_knowledge_base, upload_document,
+};
+use crate::model::knowledge::KnowledgeBase;
+use leptos::{html::Input, *};
+use uuid::Uuid;
+
+/// Renders the knowledge base section of the settings page: creating knowledge
+/// bases, adding documents to them and syncing their server-side directories.
+#[component]
+pub fn KnowledgeSettings() -> impl IntoView {
+    let (refresh, set_refresh) = create_signal(0);
+    let knowledge_bases = create_local_resource(move || refresh.get(), |_| list_knowledge_bases());
+    let (selected, set_selected) = create_signal(None::<Uuid>);
+    let (is_creating, set_is_creating) = create_signal(false);
+    let (error, set_error) = create_signal(None::<String>);
+    let (status, set_status) = create_signal(None::<String>);
+
+    let current = move || {
+        let id = selected.get()?;
+        knowledge_bases.with(|k| k.as_ref()?.as_ref().ok()?.iter().find(|k| k.id == id).cloned())
+    };
+
+    // Runs a request, then reloads the list and shows the error, if any.
+    let finish = move |result: Result<Option<String>, String>| {
+        match result {
+            Ok(message) => {
+                set_error.set(None);
+                set_status.set(message);
+            }
+            Err(e) => {
+                set_error.set(Some(e));
+                set_status.set(None);
+            }
+        }
+        set_refresh.update(|n| *n += 1);
+    };
+
+    let create = create_action(move |(name, directory): &(String, String)| {
+        let (name, directory) = (name.clone(), directory.clone());
+        async move {
+            let directory = Some(directory).filter(|d| !d.trim().is_empty());
+            match create_knowledge_base(name, directory).await {
+                Ok(knowledge_base) => {
+                    set_is_creating.set(false);
+                    set_selected.set(Some(knowledge_base.id));
+                    finish(Ok(None));
+                }
+                Err(e) => finish(Err(e)),
+            }
+        }
+    });
+
+    let upload = create_action(move |(id, files): &(Uuid, Vec<web_sys::File>)| {
+        let (id, files) = (*id, files.clone());
+        async move {
+            let mut failed = Vec::new();
+            for (i, file) in files.iter().enumerate() {
+                set_status.set(Some(format!("Indexing {} ({} of {})…", file.name(), i + 1, files.len())));
+                if let Err(e) = upload_document(id, file.clone()).await {
+                    failed.push(e);
+                }
+            }
+            finish(match failed.is_empty() {
+                true => Ok(None),
+                false => Err(failed.join("\n")),
+            });
+        }
+    });
+
+    let sync = create_action(move |id: &Uuid| {
+        let id = *id;
+        async move {
+            set_status.set(Some("Syncing…".to_string()));
+            finish(sync_knowledge_base(id).await.map(|report| match report.skipped.is_empty() {
+                true => Some(format!("Synced {} documents.", report.knowledge_base.documents.len())),
+                false => Some(format!("Synced. Skipped files without readable text: {}", report.skipped.join(", "))),
+            }));
+        }
+    });
+
+    let remove_document = create_action(move |(id, document_id): &(Uuid, Uuid)| {
+        let (id, document_id) = (*id, *document_id);
+        async move { finish(delete_document(id, document_id).await.map(|_| None)) }
+    });
+
+    let remove = create_action(move |id: &Uuid| {
+        let id = *id;
+        async move {
+            let result = delete_knowledge_base(id).await;
+            if result.is_ok() {
+                set_selected.set(None);
+            }
+            finish(result.map(|_| None));
+        }
+    });
+
+    let is_busy = move || upload.pending().get() || sync.pending().get();
+
+    view! {
+        <div class="flex gap-6">
+            <ul class="w-48 shrink-0 flex flex-col gap-1 text-sm">
+                <Transition fallback=|| ()>
+                    {move || knowledge_bases.get().map(|knowledge_bases| match knowledge_bases {
+                        Ok(knowledge_bases) => knowledge_bases.into_iter().map(|knowledge_base| {
+                            let id = knowledge_base.id;
+                            let is_selected = move || selected.get() == Some(id) && !is_creating.get();
+                            view! {
+                                <li>
+                                    <button
+                                        class="w-full text-left rounded-md px-2 py-1 hover:bg-gray-100 dark:hover:bg-gray-700"
+                                        class=("bg-gray-100", is_selected)
+                                        class=("dark:bg-gray-700", is_selected)
+                                        on:click=move |_| {
+                                            set_is_creating.set(false);
+                                            set_selected.set(Some(id));
+                                            set_status.set(None);
+                                        }
+                                    >
+                                        {knowledge_base.name}
+                                    </button>
+                                </li>
+                            }
+                        }).collect_view(),
+                        Err(e) => view! { <li class="text-red-500">{e}</li> }.into_view(),
+                    })}
+                </Transition>
+                <li>
+                    <button
+                        class="w-full text-left rounded-md px-2 py-1 text-indigo-500 hover:bg-gray-100 dark:hover:bg-gray-700"
+                        on:click=move |_| set_is_creating.set(true)
+                    >
+                        "+ New knowledge base"
+                    </button>
+                </li>
+            </ul>
+            <div class="flex-grow flex flex-col gap-2 text-sm">
+                {move || error.get().map(|e| view! { <p class="text-red-500 whitespace-pre-wrap">{e}</p> })}
+                {move || status.get().map(|s| view! { <p class="text-gray-500">{s}</p> })}
+                {move || match (is_creating.get(), current()) {
+                    (true, _) => view! { <NewKnowledgeBase on_create=move |fields| create.dispatch(fields)/> }.into_view(),
+                    (false, Some(knowledge_base)) => view! {
+                        <KnowledgeBaseDetails
+                            knowledge_base
+                            is_busy=Signal::derive(is_busy)
+                            on_upload=move |upload_request| upload.dispatch(upload_request)
+                            on_sync=move |id| sync.dispatch(id)
+                            on_remove_document=move |ids| remove_document.dispatch(ids)
+                            on_remove=move |id| remove.dispatch(id)
+                        />
+                    }.into_view(),
+                    (false, None) => view! {
+                        <p class="text-gray-500">"Select a knowledge base to manage its documents."</p>
+                    }.into_view(),
+                }}
+            </div>
+        </div>
+    }
+}
+
+#[component]
+fn NewKnowledgeBase<F>(on_create: F) -> impl IntoView
+where
+    F: Fn((String, String)) + 'static,
+{
+    let (name, set_name) = create_signal(String::new());
+    let (directory, set_directory) = create_signal(String::new());
+
+    let on_submit = move |ev: ev::SubmitEvent| {
+        ev.prevent_default();
+        on_create((name.get_untracked(), directory.get_untracked()));
+    };
+
+    view! {
+        <form class="flex flex-col gap-3" on:submit=on_submit>
+            <label class="flex flex-col gap-1">
+                "Name"
+                <input class=INPUT_CLASS prop:value=name on:input=move |ev| set_name.set(event_target_value(&ev))/>
+            </label>
+            <label class="flex flex-col gap-1">
+                "Server directory (optional)"
+                <input
+                    class=INPUT_CLASS
+                    placeholder="handbook"
+                    prop:value=directory
+                    on:input=move |ev| set_directory.set(event_target_value(&ev))
+                />
+                <span class="text-xs text-gray-500">"Relative to RUSTIC_KNOWLEDGE_DIR on the server. Its files are indexed on sync."</span>
+            </label>
+            <button type="submit" class=format!("self-start {BUTTON_CLASS}")>"Create"</button>
+        </form>
+    }
+}
+
+#[component]
+fn KnowledgeBaseDetails<U, S, R, D>(
+    knowledge_base: KnowledgeBase,
+    is_busy: Signal<bool>,
+    on_upload: U,
+    on_sync: S,
+    on_remove_document: R,
+    on_remove: D,
+) -> impl IntoView
+where
+    U: Fn((Uuid, Vec<web_sys::File>)) + 'static,
+    S: Fn(Uuid) + 'static,
+    R: Fn((Uuid, Uuid)) + Copy + 'static,
+    D: Fn(Uuid) + 'static,
+{
+    let id = knowledge_base.id;
+    let file_input_ref = create_node_ref::<Input>();
+
+    view! {
+        <h3 class="text-base font-semibold">{knowledge_base.name}</h3>
+        {knowledge_base.directory.map(|directory| view! {
+            <div class="flex gap-2 items-center">
+                <span class="text-gray-500">"Directory: " <span class="font-mono">{directory}</span></span>
+                <button
+                    type="button"
+                    class="rounded-md px-2 py-0.5 text-indigo-500 hover:bg-gray-100 dark:hover:bg-gray-700"
+                    prop:disabled=is_busy
+                    on:click=move |_| on_sync(id)
+                >
+                    "Sync"
+                </button>
+            </div>
+        })}
+        <ul class="flex flex-col divide-y dark:divide-gray-700 rounded-md border dark:border-gray-600">
+            {match knowledge_base.documents.is_empty() {
+                true => view! { <li class="px-2 py-1 text-gray-500">"No documents yet."</li> }.into_view(),
+                false => knowledge_base.documents.into_iter().map(|document| {
+                    let document_id = document.id;
+                    view! {
+                        <li class="flex justify-between gap-2 px-2 py-1">
+                            <span class="truncate" title=document.path.unwrap_or_default()>{document.name}</span>
+                            <span class="shrink-0 text-gray-500">
+                                {format!("{} chunks", document.chunks)}
+                                <button
+                                    type="button"
+                                    class="ml-2 hover:text-red-500"
+                                    title="Remove document"
+                                    on:click=move |_| on_remove_document((id, document_id))
+                                >
+                                    "×"
+                                </button>
+                            </span>
+                        </li>
+                    }
+                }).collect_view(),
+            }}
+        </ul>
+        <div class="flex gap-2">
+            <input
+                type="file"
+                multiple
+                class="hidden"
+                node_ref=file_input_ref
+                on:change=move |_| {
+                    let Some(input) = file_input_ref.get() else { return };
+                    if let Some(files) = input.files() {
+                        on_upload((id, (0..files.length()).filter_map(|i| files.get(i)).collect()));
+                    }
+                    input.set_value("");
+                }
+            />
+            <button
+                type="button"
+                class=BUTTON_CLASS
+                prop:disabled=is_busy
+                on:click=move |_| if let Some(input) = file_input_ref.get() { input.click() }
+            >
+                "Add documents"
+            </button>
+            <button
+                type="button"
+                class="rounded-md px-3 py-1.5 text-red-500 hover:bg-gray-100 dark:hover:bg-gray-700"
+                on:click=move |_| on_remove(id)
+            >
+                "Delete"
+            </button>
+        </div>
+    }
+}
@@ -0,0 +1,170 @@
+use super::{BUTTON_CLASS, INPUT_CLASS};
+use crate::app::components::template_picker::{delete_template, list_templates, save_template};
+use crate::model::template::PromptTemplate;
+use leptos::*;
+use uuid::Uuid;
+
+/// Renders the prompt template section of the settings page. Templates owned by
+/// other browsers are listed read-only.
+#[component]
+pub fn TemplateSettings() -> impl IntoView {
+    let (refresh, set_refresh) = create_signal(0);
+    let templates = create_local_resource(move || refresh.get(), |_| list_templates());
+    let draft = create_rw_signal(None::<PromptTemplate>);
+    let (error, set_error) = create_signal(None::<String>);
+
+    let save = create_action(move |template: &PromptTemplate| {
+        let template = template.clone();
+        async move {
+            match save_template(&template).await {
+                Ok(saved) => {
+                    draft.set(Some(saved));
+                    set_error.set(None);
+                }
+                Err(e) => set_error.set(Some(e)),
+            }
+            set_refresh.update(|n| *n += 1);
+        }
+    });
+
+    let delete = create_action(move |id: &Uuid| {
+        let id = *id;
+        async move {
+            match delete_template(id).await {
+                Ok(()) => {
+                    draft.set(None);
+                    set_error.set(None);
+                }
+                Err(e) => set_error.set(Some(e)),
+            }
+            set_refresh.update(|n| *n += 1);
+        }
+    });
+
+    let is_new = move || {
+        let id = draft.with(|d| d.as_ref().map(|d| d.id));
+        !templates.with(|t| t.as_ref().and_then(|t| t.as_ref().ok()).is_some_and(|t| t.iter().any(|t| Some(t.id) == id)))
+    };
+    let is_editable = move || draft.with(|d| d.as_ref().is_some_and(|d| d.editable));
+
+    let on_submit = move |ev: ev::SubmitEvent| {
+        ev.prevent_default();
+        if let Some(template) = draft.get_untracked() {
+            save.dispatch(template);
+        }
+    };
+
+    view! {
+        <div class="flex gap-6">
+            <ul class="w-48 shrink-0 flex flex-col gap-1 text-sm">
+                <Transition fallback=|| ()>
+                    {move || templates.get().map(|templates| match templates {
+                        Ok(templates) => templates.into_iter().map(|template| {
+                            let id = template.id;
+                            let name = template.name.clone();
+                            let shared = template.shared;
+                            let is_selected = move || draft.with(|d| d.as_ref().is_some_and(|d| d.id == id));
+                            view! {
+                                <li>
+                                    <button
+                                        class="w-full flex justify-between text-left rounded-md px-2 py-1 hover:bg-gray-100 dark:hover:bg-gray-700"
+                                        class=("bg-gray-100", is_selected)
+                                        class=("dark:bg-gray-700", is_selected)
+                                        on:click=move |_| draft.set(Some(template.clone()))
+                                    >
+                                        <span>{name}</span>
+                                        {shared.then(|| view! { <span class="text-xs text-gray-500">"shared"</span> })}
+                                    </button>
+                                </li>
+                            }
+                        }).collect_view(),
+                        Err(e) => view! { <li class="text-red-500">{e}</li> }.into_view(),
+                    })}
+                </Transition>
+                <li>
+                    <button
+                        class="w-full text-left rounded-md px-2 py-1 text-indigo-500 hover:bg-gray-100 dark:hover:bg-gray-700"
+                        on:click=move |_| draft.set(Some(PromptTemplate::new("New template".to_string())))
+                    >
+                        "+ New template"
+                    </button>
+                </li>
+            </ul>
+            <div class="flex-grow">
+                {move || error.get().map(|e| view! { <p class="mb-2 text-sm text-red-500">{e}</p> })}
+                <Show
+                    when=move || draft.with(Option::is_some)
+                    fallback=|| view! { <p class="text-sm text-gray-500">"Select a template to edit it."</p> }
+                >
+                    <form class="flex flex-col gap-3 text-sm" on:submit=on_submit>
+                        <fieldset class="contents" prop:disabled=move || !is_editable()>
+                            <label class="flex flex-col gap-1">
+                                "Name"
+                                <input
+                                    class=INPUT_CLASS
+                                    prop:value=move || draft.with(|d| d.as_ref().map(|d| d.name.clone()).unwrap_or_default())
+                                    on:input=move |ev| {
+                                        let value = event_target_value(&ev);
+                                        draft.update(|d| if let Some(d) = d { d.name = value });
+                                    }
+                                />
+                            </label>
+                            <label class="flex flex-col gap-1">
+                                "Template"
+                                <textarea
+                                    class=INPUT_CLASS
+                                    rows=6
+                                    placeholder="Summarize {{text}} in {{language}}."
+                                    prop:value=move || draft.with(|d| d.as_ref().map(|d| d.body.clone()).unwrap_or_default())
+                                    on:input=move |ev| {
+                                        let value = event_target_value(&ev);
+                                        draft.update(|d| if let Some(d) = d { d.body = value });
+                                    }
+                                />
+                            </label>
+                            <p class="text-xs text-gray-500">
+                                "Variables: "
+                                {move || {
+                                    let variables = draft.with(|d| d.as_ref().map(PromptTemplate::variables).unwrap_or_default());
+                                    if variables.is_empty() { "none".to_string() } else { variables.join(", ") }
+                                }}
+                            </p>
+                            <label class="flex gap-2 items-center">
+                                <input
+                                    type="checkbox"
+                                    prop:checked=move || draft.with(|d| d.as_ref().is_some_and(|d| d.shared))
+                                    on:change=move |ev| {
+                                        let checked = event_target_checked(&ev);
+                                        draft.update(|d| if let Some(d) = d { d.shared = checked });
+                                    }
+                                />
+                                "Share with everyone using this server"
+                            </label>
+                        </fieldset>
+                        <Show
+                            when=is_editable
+                            fallback=|| view! { <p class="text-xs text-gray-500">"Shared by someone else; only they can change it."</p> }
+                        >
+                            <div class="flex gap-2">
+                                <button type="submit" class=BUTTON_CLASS>"Save"</button>
+                                <Show when=move || !is_new()>
+                                    <button
+                                        type="button"
+                                        class="rounded-md px-3 py-1.5 text-red-500 hover:bg-gray-100 dark:hover:bg-gray-700"
+                                        on:click=move |_| {
+                                            if let Some(id) = draft.with_untracked(|d| d.as_ref().map(|d| d.id)) {
+                                                delete.dispatch(id);
+                                            }
+                                        }
+                                    >
+                                        "Delete"
+                                    </button>
+                                </Show>
+                            </div>
+                        </Show>
+                    </form>
+                </Show>
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,124 @@
+use crate::app::components::chat_area::Markdown;
+use crate::model::share::{ShareView, SharedConversation};
+use chrono::DateTime;
+use leptos::{html::Input, *};
+use leptos_meta::*;
+use leptos_router::*;
+
+/// Resolves a share link for the public page.
+#[server(GetShare, "/api")]
+pub async fn get_share(token: String, password: Option<String>) -> Result<Option<ShareView>, ServerFnError> {
+    use crate::api::store::Store;
+    use actix_web::web;
+
+    let store: web::Data<Store> = leptos_actix::extract().await?;
+    // Checking a password takes a deliberately slow hash, so it runs off the async workers.
+    web::block(move || crate::api::shares::open(&store, &token, password.as_deref()))
+        .await?
+        .map_err(ServerFnError::new)
+}
+
+/// Renders the read-only view of a shared conversation at `/s/:token`.
+#[component]
+pub fn SharePage() -> impl IntoView {
+    let params = use_params_map();
+    let token = move || params.with(|p| p.get("token").cloned().unwrap_or_default());
+    let (password, set_password) = create_signal(None::<String>);
+    let share = create_resource(move || (token(), password.get()), |(token, password)| get_share(token, password));
+
+    view! {
+        <Title text="Shared conversation"/>
+        <Suspense fallback=|| view! { <p class="mt-10 text-center text-gray-500">"Loading…"</p> }>
+            {move || share.get().map(|result| match result {
+                Ok(Some(ShareView::Available(shared))) => view! { <SharedConversationView shared/> }.into_view(),
+                Ok(Some(ShareView::PasswordRequired { incorrect })) => {
+                    view! { <PasswordForm incorrect set_password/> }.into_view()
+                }
+                Ok(Some(ShareView::TooManyAttempts { retry_after_secs })) => view! {
+                    <ShareNotice
+                        title="Too many attempts"
+                        message=format!("Too many wrong passwords were tried. Try again in {retry_after_secs} s.")
+                    />
+                }.into_view(),
+                Ok(None) => view! {
+                    <ShareNotice
+                        title="Link unavailable"
+                        message="This share link does not exist, has expired or was revoked."
+                    />
+                }.into_view(),
+                Err(e) => view! { <ShareNotice title="Something went wrong" message=e.to_string()/> }.into_view(),
+            })}
+        </Suspense>
+    }
+}
+
+#[component]
+fn SharedConversationView(shared: SharedConversation) -> impl IntoView {
+    let created = format_timestamp(shared.created_at);
+    let expires = shared.expires_at.map(format_timestamp);
+
+    view! {
+        <div class="py-10">
+            <h1 class="text-2xl font-semibold px-5">{shared.name}</h1>
+            <p class="px-5 mt-1 text-sm text-gray-500">
+                "Shared " {created} " · read-only"
+                {expires.map(|expires| format!(" · expires {expires}"))}
+            </p>
+            <div class="mt-6 flex flex-col px-5">
+                {shared.messages.into_iter().map(|message| view! {
+                    <div class="w-full border-b border-gray-700 last:border-b-0 py-4">
+                        <p class="text-xs font-semibold uppercase tracking-wide text-gray-500 mb-2">
+                            {message.role.label()}
+                        </p>
+                        <Markdown markdown=message.content/>
+                    </div>
+                }).collect_view()}
+            </div>
+        </div>
+    }
+}
+
+#[component]
+fn PasswordForm(incorrect: bool, set_password: WriteSignal<Option<String>>) -> impl IntoView {
+    let input_ref = create_node_ref::<Input>();
+
+    let on_submit = move |ev: ev::SubmitEvent| {
+        ev.prevent_default();
+        if let Some(input) = input_ref.get() {
+            set_password.set(Some(input.value()));
+        }
+    };
+
+    view! {
+        <form class="mt-24 mx-auto max-w-sm flex flex-col gap-3" on:submit=on_submit>
+            <h1 class="text-xl font-semibold">"This conversation is password protected"</h1>
+            <input
+                type="password"
+                placeholder="Password"
+                node_ref=input_ref
+                class="rounded-lg border dark:border-gray-600 dark:bg-gray-800 px-3 py-2 outline-none"
+            />
+            {incorrect.then(|| view! { <p class="text-sm text-red-500">"Incorrect password."</p> })}
+            <button
+                type="submit"
+                class="px-4 py-2 font-medium text-white bg-indigo-500 rounded-md hover:bg-indigo-600 transition-all duration-200 ease-in-out"
+            >
+                "View conversation"
+            </button>
+        </form>
+    }
+}
+
+#[component]
+fn ShareNotice(title: &'static str, #[prop(into)] message: String) -> impl IntoView {
+    view! {
+        <div class="mt-24 flex flex-col items-center text-center gap-2">
+            <h1 class="text-2xl font-semibold">{title}</h1>
+            <p class="text-gray-500">{message}</p>
+        </div>
+    }
+}
+
+fn format_timestamp(secs: i64) -> String {
+    DateTime::from_timestamp(secs, 0).map_or_else(String::new, |t| t.format("%Y-%m-%d %H:%M UTC").to_string())
+}
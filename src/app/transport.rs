@@ -0,0 +1,154 @@
+use crate::model::event::{ChatEvent, ClientMessage};
+use futures::lock::Mutex;
+use futures::StreamExt;
+use futures_util::sink::SinkExt;
+use futures_util::stream::SplitSink;
+use gloo_net::http::Request;
+use gloo_net::websocket::futures::WebSocket;
+use gloo_net::websocket::Message as WsMessage;
+use leptos::*;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+/// Client side of the chat protocol.
+///
+/// Prompts and commands go over the `/ws` socket while it is usable. Once the upgrade
+/// fails or the socket errors, every message is POSTed to `/sse` instead and the reply
+/// is read back as Server-Sent Events carrying the same `ChatEvent`s.
+pub struct Transport {
+    /// Locked for the whole of a send, so messages sent at the same time go out over
+    /// the socket one after the other, in order.
+    ws_sender: Mutex<Option<SplitSink<WebSocket, WsMessage>>>,
+    use_sse: Cell<bool>,
+    /// The last message sent over the socket that has not produced any event yet,
+    /// replayed over SSE if the socket turns out to be dead.
+    pending: RefCell<Option<ClientMessage>>,
+    on_event: Box<dyn Fn(ChatEvent)>,
+}
+
+impl Transport {
+    pub fn connect(on_event: impl Fn(ChatEvent) + 'static) -> Rc<Self> {
+        let transport = Rc::new(Transport {
+            ws_sender: Mutex::new(None),
+            use_sse: Cell::new(false),
+            pending: RefCell::new(None),
+            on_event: Box::new(on_event),
+        });
+
+        let location = web_sys::window().unwrap().location();
+        let hostname = location.hostname().expect("failed to retrieve origin hostname");
+        let ws_url = format!("ws://{hostname}:3000/ws");
+
+        let ws = match WebSocket::open(&ws_url) {
+            Ok(ws) => ws,
+            Err(_) => {
+                transport.use_sse.set(true);
+                return transport;
+            }
+        };
+        let (sender, mut receiver) = ws.split();
+        *transport.ws_sender.try_lock().expect("no send before the socket is open") = Some(sender);
+
+        let this = transport.clone();
+        spawn_local(async move {
+            while let Some(msg) = receiver.next().await {
+                match msg {
+                    Ok(WsMessage::Text(text)) => {
+                        this.pending.borrow_mut().take();
+                        match serde_json::from_str::<ChatEvent>(&text) {
+                            Ok(event) => (this.on_event)(event),
+                            Err(e) => (this.on_event)(ChatEvent::Error { message: e.to_string() }),
+                        }
+                    }
+                    Err(_) => break,
+                    _ => {}
+                }
+            }
+
+            this.use_sse.set(true);
+            this.ws_sender.lock().await.take();
+
+            let pending = this.pending.borrow_mut().take();
+            if let Some(message) = pending {
+                if let Err(e) = this.send_sse(&message).await {
+                    (this.on_event)(ChatEvent::Error { message: e.to_string() });
+                }
+            }
+        });
+
+        transport
+    }
+
+    pub async fn send(&self, message: ClientMessage) -> Result<(), ServerFnError> {
+        if !self.use_sse.get() {
+            let mut ws_sender = self.ws_sender.lock().await;
+            if let Some(sender) = ws_sender.as_mut() {
+                let text = serde_json::to_string(&message)
+                    .map_err(|e| ServerFnError::new(e.to_string()))?;
+                *self.pending.borrow_mut() = Some(message.clone());
+                if sender.send(WsMessage::Text(text)).await.is_ok() && !self.use_sse.get() {
+                    return Ok(());
+                }
+                ws_sender.take();
+                drop(ws_sender);
+                // The receiver loop replays the pending message once it notices the socket died.
+                if self.use_sse.get() {
+                    return Ok(());
+                }
+                self.pending.borrow_mut().take();
+                self.use_sse.set(true);
+            }
+        }
+
+        self.send_sse(&message).await
+    }
+
+    async fn send_sse(&self, message: &ClientMessage) -> Result<(), ServerFnError> {
+        let response = Request::post("/sse")
+            .json(message)
+            .map_err(|e| ServerFnError::new(e.to_string()))?
+            .send()
+            .await
+            .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+        if !response.ok() {
+            return Err(ServerFnError::ServerError(format!("SSE request failed with status {}", response.status())));
+        }
+
+        let body = response
+            .body()
+            .ok_or_else(|| ServerFnError::new("SSE response has no body"))?;
+        let mut chunks = wasm_streams::ReadableStream::from_raw(body).into_stream();
+        let mut buffer: Vec<u8> = Vec::new();
+
+        while let Some(chunk) = chunks.next().await {
+            let chunk = chunk.map_err(|_| ServerFnError::new("SSE stream error"))?;
+            buffer.extend(js_sys::Uint8Array::new(&chunk).to_vec());
+
+            while let Some(end) = buffer.windows(2).position(|w| w == b"\n\n") {
+                let frame: Vec<u8> = buffer.drain(..end + 2).collect();
+                if let Some(event) = parse_sse_frame(&String::from_utf8_lossy(&frame)) {
+                    (self.on_event)(event);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Extracts the `ChatEvent` from the `data:` lines of a single SSE frame.
+fn parse_sse_frame(frame: &str) -> Option<ChatEvent> {
+    let data = frame
+        .lines()
+        .filter_map(|line| line.strip_prefix("data:"))
+        .map(str::trim_start)
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    if data.is_empty() {
+        return None;
+    }
+
+    Some(serde_json::from_str(&data).unwrap_or_else(|e| ChatEvent::Error { message: e.to_string() }))
+}
@@ -0,0 +1,267 @@
+//! Terminal chat client for a running rustic-ai server.
+//!
+//! Talks to the server's OpenAI-compatible REST API, streams replies and renders
+//! their Markdown as ANSI. Run it with `cargo run --features cli --bin rustic-ai-cli`.
+
+mod render;
+
+use awc::Client;
+use futures::StreamExt;
+use render::Renderer;
+use rustic_ai::model::conversation::{Conversation, Message, Role};
+use serde_json::{json, Value};
+use std::env;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::time::Duration;
+
+const HELP: &str = "\
+Commands:
+  /help            show this help
+  /models          list the models served by the backend
+  /model [NAME]    show or switch the model used for new replies
+  /new [NAME]      start a new conversation
+  /list            list conversations of this session
+  /switch N        continue conversation number N
+  /clear           forget the messages of the current conversation
+  /quit            exit (Ctrl-D works too)
+End a line with `\\` to continue the prompt on the next line.";
+
+struct Cli {
+    client: Client,
+    server: String,
+    model: String,
+    color: bool,
+    conversations: Vec<Conversation>,
+    current: usize,
+}
+
+#[actix_rt::main]
+async fn main() {
+    let mut server = env::var("RUSTIC_AI_URL").unwrap_or_else(|_| "http://127.0.0.1:3000".to_string());
+    let mut model = String::new();
+    let mut color = io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none();
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--server" | "-s" => server = args.next().expect("--server needs a URL"),
+            "--model" | "-m" => model = args.next().expect("--model needs a name"),
+            "--raw" => color = false,
+            "--help" | "-h" => {
+                println!("Usage: rustic-ai-cli [--server URL] [--model NAME] [--raw]\n\n{HELP}");
+                return;
+            }
+            other => {
+                eprintln!("unknown argument `{other}`, see --help");
+                std::process::exit(2);
+            }
+        }
+    }
+
+    let mut cli = Cli {
+        client: Client::builder().timeout(Duration::from_secs(600)).finish(),
+        server: server.trim_end_matches('/').to_string(),
+        model,
+        color,
+        conversations: vec![Conversation::new("Conversation 1".to_string())],
+        current: 0,
+    };
+
+    println!("rustic-ai @ {} — /help for commands", cli.server);
+    cli.repl().await;
+}
+
+impl Cli {
+    async fn repl(&mut self) {
+        let stdin = io::stdin();
+        let mut lines = stdin.lock().lines();
+
+        loop {
+            print!("{}", if self.color { "\x1b[1;32m> \x1b[0m" } else { "> " });
+            io::stdout().flush().ok();
+
+            let mut input = String::new();
+            loop {
+                let Some(Ok(line)) = lines.next() else { return };
+                match line.strip_suffix('\\') {
+                    Some(line) => {
+                        input.push_str(line);
+                        input.push('\n');
+                    }
+                    None => {
+                        input.push_str(&line);
+                        break;
+                    }
+                }
+            }
+
+            let input = input.trim();
+            if input.is_empty() {
+                continue;
+            }
+
+            let result = match input.strip_prefix('/') {
+                Some(command) => self.command(command).await,
+                None => self.chat(input).await,
+            };
+
+            match result {
+                Ok(true) => {}
+                Ok(false) => return,
+                Err(e) => eprintln!("error: {e}"),
+            }
+        }
+    }
+
+    /// Runs a REPL command, returning `Ok(false)` when the session should end.
+    async fn command(&mut self, command: &str) -> Result<bool, String> {
+        let (name, arg) = command.split_once(' ').map_or((command, ""), |(n, a)| (n, a.trim()));
+
+        match name {
+            "help" => println!("{HELP}"),
+            "quit" | "exit" => return Ok(false),
+            "models" => {
+                for model in self.models().await? {
+                    let marker = if model == self.model { "*" } else { " " };
+                    println!("{marker} {model}");
+                }
+            }
+            "model" if arg.is_empty() => {
+                println!("{}", if self.model.is_empty() { "(server default)" } else { &self.model })
+            }
+            "model" => {
+                self.model = arg.to_string();
+                println!("model set to {}", self.model);
+            }
+            "new" => {
+                let name = if arg.is_empty() {
+                    format!("Conversation {}", self.conversations.len() + 1)
+                } else {
+                    arg.to_string()
+                };
+                self.conversations.push(Conversation::new(name));
+                self.current = self.conversations.len() - 1;
+                println!("started {}", self.conversations[self.current].name);
+            }
+            "list" => {
+                for (i, conversation) in self.conversations.iter().enumerate() {
+                    let marker = if i == self.current { "*" } else { " " };
+                    println!("{marker} {}. {} ({} messages)", i + 1, conversation.name, conversation.messages.len());
+                }
+            }
+            "switch" => {
+                let n: usize = arg.parse().map_err(|_| "usage: /switch N".to_string())?;
+                if n == 0 || n > self.conversations.len() {
+                    return Err(format!("no conversation number {n}"));
+                }
+                self.current = n - 1;
+                println!("switched to {}", self.conversations[self.current].name);
+            }
+            "clear" => {
+                self.conversations[self.current].messages.clear();
+                println!("cleared {}", self.conversations[self.current].name);
+            }
+            other => return Err(format!("unknown command `/{other}`, see /help")),
+        }
+
+        Ok(true)
+    }
+
+    async fn models(&self) -> Result<Vec<String>, String> {
+        let mut res = self.client
+            .get(format!("{}/v1/models", self.server))
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        let body: Value = res.json().await.map_err(|e| e.to_string())?;
+
+        Ok(body["data"]
+            .as_array()
+            .map(|models| models.iter().filter_map(|m| m["id"].as_str().map(str::to_string)).collect())
+            .unwrap_or_default())
+    }
+
+    /// Sends `prompt` within the current conversation and streams the reply to stdout.
+    /// When the request or the stream fails, the prompt is taken back out of the
+    /// conversation, so it can be sent again without appearing twice.
+    async fn chat(&mut self, prompt: &str) -> Result<bool, String> {
+        let conversation = &mut self.conversations[self.current];
+        conversation.messages.push(Message::new(Role::User, prompt.to_string()));
+
+        let messages: Vec<Value> = conversation.messages
+            .iter()
+            .map(|m| json!({ "role": m.role, "content": m.content }))
+            .collect();
+
+        let result = self.stream_reply(&messages).await;
+        let conversation = &mut self.conversations[self.current];
+        match result {
+            Ok(reply) => conversation.messages.push(Message::new(Role::Assistant, reply)),
+            Err(e) => {
+                conversation.messages.pop();
+                return Err(e);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Streams the reply to `messages` to stdout and returns it.
+    async fn stream_reply(&self, messages: &[Value]) -> Result<String, String> {
+        let mut res = self.client
+            .post(format!("{}/v1/chat/completions", self.server))
+            .send_json(&json!({ "model": self.model, "messages": messages, "stream": true }))
+            .await
+            .map_err(|e| e.to_string())?;
+
+        if !res.status().is_success() {
+            let body = res.body().await.map_err(|e| e.to_string())?;
+            return Err(format!("{}: {}", res.status(), String::from_utf8_lossy(&body)));
+        }
+
+        let mut renderer = Renderer::new(self.color);
+        let mut reply = String::new();
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut stdout = io::stdout();
+        let mut failure = None;
+
+        'stream: while let Some(chunk) = res.next().await {
+            match chunk {
+                Ok(chunk) => buffer.extend_from_slice(&chunk),
+                Err(e) => {
+                    failure = Some(e.to_string());
+                    break;
+                }
+            }
+
+            while let Some(end) = buffer.windows(2).position(|w| w == b"\n\n") {
+                let frame: Vec<u8> = buffer.drain(..end + 2).collect();
+                let frame = String::from_utf8_lossy(&frame);
+                let Some(data) = frame.trim().strip_prefix("data:").map(str::trim) else { continue };
+                if data == "[DONE]" {
+                    break 'stream;
+                }
+
+                let chunk: Value = match serde_json::from_str(data) {
+                    Ok(chunk) => chunk,
+                    Err(e) => {
+                        failure = Some(e.to_string());
+                        break 'stream;
+                    }
+                };
+                if let Some(content) = chunk["choices"][0]["delta"]["content"].as_str() {
+                    reply.push_str(content);
+                    write!(stdout, "{}", renderer.push(content)).ok();
+                    stdout.flush().ok();
+                }
+            }
+        }
+
+        // The partly printed reply is ended before any error is reported.
+        writeln!(stdout, "{}", renderer.finish()).ok();
+        match failure {
+            Some(e) => Err(e),
+            None => Ok(reply),
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const ITALIC: &str = "\x1b[3m";
+const UNDERLINE: &str = "\x1b[4m";
+const STRIKE: &str = "\x1b[9m";
+const CYAN: &str = "\x1b[36m";
+const YELLOW: &str = "\x1b[33m";
+const MAGENTA: &str = "\x1b[35m";
+
+/// Renders streamed Markdown to ANSI one completed line at a time, keeping track of
+/// whether the stream is inside a fenced code block.
+pub struct Renderer {
+    color: bool,
+    in_code_block: bool,
+    line: String,
+}
+
+impl Renderer {
+    pub fn new(color: bool) -> Renderer {
+        Renderer { color, in_code_block: false, line: String::new() }
+    }
+
+    /// Buffers `chunk` and returns the rendering of every line it completed.
+    pub fn push(&mut self, chunk: &str) -> String {
+        if !self.color {
+            return chunk.to_string();
+        }
+
+        let mut out = String::new();
+        self.line.push_str(chunk);
+        while let Some(end) = self.line.find('\n') {
+            let line: String = self.line.drain(..=end).collect();
+            out.push_str(&self.render_line(line.trim_end_matches('\n')));
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Renders whatever is left of the last line and resets the block state.
+    pub fn finish(&mut self) -> String {
+        let line = std::mem::take(&mut self.line);
+        let out = if line.is_empty() { String::new() } else { self.render_line(&line) };
+        self.in_code_block = false;
+        out
+    }
+
+    fn render_line(&mut self, line: &str) -> String {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            self.in_code_block = !self.in_code_block;
+            return format!("{DIM}{line}{RESET}");
+        }
+        if self.in_code_block {
+            return format!("{CYAN}{line}{RESET}");
+        }
+        if trimmed.starts_with('|') {
+            return line.to_string();
+        }
+        render_inline(line)
+    }
+}
+
+/// Renders a single line of Markdown with pulldown-cmark, mapping its events to ANSI styles.
+fn render_inline(line: &str) -> String {
+    let mut out = String::new();
+    let mut ordered: Vec<Option<u64>> = Vec::new();
+    let mut link: Option<String> = None;
+
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+
+    for event in Parser::new_ext(line, options) {
+        match event {
+            Event::Start(Tag::Heading { .. }) => out.push_str(&format!("{BOLD}{UNDERLINE}{MAGENTA}")),
+            Event::Start(Tag::BlockQuote(_)) => out.push_str(&format!("{DIM}│ {RESET}")),
+            Event::Start(Tag::List(start)) => ordered.push(start),
+            Event::End(TagEnd::List(_)) => {
+                ordered.pop();
+            }
+            Event::Start(Tag::Item) => {
+                let indent = "  ".repeat(ordered.len().saturating_sub(1));
+                match ordered.last_mut() {
+                    Some(Some(n)) => {
+                        out.push_str(&format!("{indent}{n}. "));
+                        *n += 1;
+                    }
+                    _ => out.push_str(&format!("{indent}• ")),
+                }
+            }
+            Event::Start(Tag::Strong) => out.push_str(BOLD),
+            Event::Start(Tag::Emphasis) => out.push_str(ITALIC),
+            Event::Start(Tag::Strikethrough) => out.push_str(STRIKE),
+            Event::Start(Tag::Link { dest_url, .. }) => {
+                out.push_str(UNDERLINE);
+                link = Some(dest_url.to_string());
+            }
+            Event::End(TagEnd::Heading(_) | TagEnd::Strong | TagEnd::Emphasis | TagEnd::Strikethrough) => {
+                out.push_str(RESET)
+            }
+            Event::End(TagEnd::Link) => {
+                out.push_str(RESET);
+                if let Some(url) = link.take() {
+                    out.push_str(&format!(" {DIM}({url}){RESET}"));
+                }
+            }
+            Event::Text(text) => out.push_str(&text),
+            Event::Code(code) => out.push_str(&format!("{YELLOW}{code}{RESET}")),
+            Event::Html(html) | Event::InlineHtml(html) => out.push_str(&html),
+            Event::SoftBreak | Event::HardBreak => out.push(' '),
+            Event::Rule => out.push_str(&format!("{DIM}{}{RESET}", "─".repeat(40))),
+            _ => {}
+        }
+    }
+
+    out
+}
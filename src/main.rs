@@ -1,7 +1,3 @@
-pub mod api;
-pub mod model;
-use crate::api::ws;
-
 #[cfg(feature = "ssr")]
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
@@ -10,6 +6,9 @@ async fn main() -> std::io::Result<()> {
     use leptos::*;
     use leptos_actix::{generate_route_list, LeptosRoutes};
     use ollama_rs::Ollama;
+    use rustic_ai::api::{attachments, conversations, import, knowledge, openai, personas, search, shares, sse, templates, tools, ws, Sessions};
+    use rustic_ai::api::search::SearchIndex;
+    use rustic_ai::api::store::Store;
     use rustic_ai::app::*;
 
     let conf = get_configuration(None).await.unwrap();
@@ -18,6 +17,12 @@ async fn main() -> std::io::Result<()> {
     let routes = generate_route_list(App);
     println!("listening on http://{}", &addr);
 
+    env_logger::init_from_env(env_logger::Env::new().default_filter_or("info"));
+
+    let sessions = web::Data::new(Sessions::default());
+    let store = web::Data::new(Store::from_env());
+    let search_index = web::Data::new(SearchIndex::default());
+
     HttpServer::new(move || {
         let leptos_options = &conf.leptos_options;
         let site_root = &leptos_options.site_root;
@@ -33,9 +38,77 @@ async fn main() -> std::io::Result<()> {
             .service(favicon)
             .service(web::resource("/ws")
             .route(web::get().to(ws)))
+            .service(web::resource("/sse")
+            .route(web::post().to(sse)))
+            .service(web::resource("/v1/chat/completions")
+            .route(web::post().to(openai::chat_completions)))
+            .service(web::resource("/v1/models")
+            .route(web::get().to(openai::models)))
+            .service(web::resource("/api/conversations")
+            .route(web::get().to(conversations::list)))
+            .service(web::resource("/api/conversations/export")
+            .route(web::get().to(conversations::export_many)))
+            .service(web::resource("/api/conversations/{id}")
+            .route(web::get().to(conversations::get)))
+            .service(web::resource("/api/conversations/{id}/messages")
+            .route(web::get().to(conversations::messages)))
+            .service(web::resource("/api/conversations/{id}/export")
+            .route(web::get().to(conversations::export_one)))
+            .service(web::resource("/api/conversations/{id}/shares")
+            .route(web::get().to(shares::list))
+            .route(web::post().to(shares::create)))
+            .service(web::resource("/api/shares/{token}")
+            .route(web::delete().to(shares::revoke)))
+            .service(web::resource("/api/personas")
+            .route(web::get().to(personas::list))
+            .route(web::post().to(personas::create)))
+            .service(web::resource("/api/personas/{id}")
+            .route(web::get().to(personas::get))
+            .route(web::put().to(personas::update))
+            .route(web::delete().to(personas::delete)))
+            .service(web::resource("/api/templates")
+            .route(web::get().to(templates::list))
+            .route(web::post().to(templates::create)))
+            .service(web::resource("/api/templates/{id}")
+            .route(web::put().to(templates::update))
+            .route(web::delete().to(templates::delete)))
+            .service(web::resource("/api/search")
+            .route(web::get().to(search::search)))
+            .service(web::resource("/api/attachments")
+            // PDFs and source dumps run larger than the default limit
+            .app_data(web::PayloadConfig::new(32 * 1024 * 1024))
+            .route(web::post().to(attachments::upload)))
+            .service(web::resource("/api/attachments/{id}")
+            .route(web::get().to(attachments::get)))
+            .service(web::resource("/api/tools")
+            .route(web::get().to(tools::list)))
+            .service(web::resource("/api/knowledge")
+            .route(web::get().to(knowledge::list))
+            .route(web::post().to(knowledge::create)))
+            .service(web::resource("/api/knowledge/{id}")
+            .route(web::get().to(knowledge::get))
+            .route(web::delete().to(knowledge::delete)))
+            .service(web::resource("/api/knowledge/{id}/documents")
+            .app_data(web::PayloadConfig::new(32 * 1024 * 1024))
+            .route(web::post().to(knowledge::upload)))
+            .service(web::resource("/api/knowledge/{id}/documents/{document_id}")
+            .route(web::delete().to(knowledge::delete_document)))
+            .service(web::resource("/api/knowledge/{id}/documents/{document_id}/chunks/{chunk}")
+            .route(web::get().to(knowledge::chunk_text)))
+            .service(web::resource("/api/knowledge/{id}/sync")
+            .route(web::post().to(knowledge::sync)))
+            .service(web::resource("/api/import")
+            // chat-app exports hold years of history
+            .app_data(web::PayloadConfig::new(256 * 1024 * 1024))
+            .route(web::post().to(import::import)))
+            .route("/api/{tail:.*}", leptos_actix::handle_server_fns())
             .leptos_routes(leptos_options.to_owned(), routes.to_owned(), App)
             .app_data(ollama.clone())
+            .app_data(sessions.clone())
+            .app_data(store.clone())
+            .app_data(search_index.clone())
             .app_data(web::Data::new(leptos_options.to_owned()))
+            .wrap(middleware::Logger::default())
         //.wrap(middleware::Compress::default())
     })
     .bind(&addr)?
@@ -0,0 +1,324 @@
+use ammonia::UrlRelative;
+use latex2mathml::DisplayStyle;
+use pulldown_cmark::{html, CodeBlockKind, Event, Options, Parser, Tag, TagEnd};
+use std::borrow::Cow;
+use std::sync::OnceLock;
+use syntect::html::{ClassStyle, ClassedHTMLGenerator};
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+use syntect::util::LinesWithEndings;
+
+/// Prefix of the classes highlighted tokens get; `style/_highlight.scss` colors them.
+const CLASS_STYLE: ClassStyle = ClassStyle::SpacedPrefixed { prefix: "hl-" };
+
+/// Renders model output from Markdown to HTML, highlighting fenced code blocks whose
+/// language is known. Each code block gets a toolbar with its language and Copy,
+/// Download and Wrap buttons, which the `Markdown` component handles. Inline `$...$` and
+/// display `$$...$$` math is converted to MathML, which browsers render without scripts.
+///
+/// Model output can contain raw HTML, including whatever a prompt-injected document
+/// made it write, so the result is passed through an allow-list sanitizer: scripts,
+/// event handlers, styles and `javascript:` URLs are removed and links get
+/// `rel="noopener noreferrer"`.
+///
+/// Shared by the `Markdown` component and the server-side exports so both show
+/// messages the same way.
+pub fn render(markdown: &str) -> String {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_MATH);
+
+    let parser = Parser::new_ext(markdown, options);
+    let mut html_output = String::new();
+    html::push_html(&mut html_output, render_math(highlight_code_blocks(parser)));
+
+    sanitizer().clean(&html_output).to_string()
+}
+
+/// Splits Markdown into runs of top-level blocks that render the same with
+/// [`render_block`] as within the whole text, so a message being streamed only needs
+/// its last block rendered again as tokens arrive. Splits are made before an unindented
+/// line that follows a blank line, outside code fences. Lines starting a list item are
+/// left joined to what comes before, as they may continue a loose list, and so is
+/// everything after raw HTML, which may run across blank lines.
+///
+/// A reference-style link such as `[1]` only resolves when its definition is rendered
+/// with it, so `render_block` is given the [`link_definitions`] of the whole text.
+pub fn blocks(markdown: &str) -> Vec<&str> {
+    let mut blocks = Vec::new();
+    let mut start = 0;
+    let mut offset = 0;
+    let mut fence: Option<&str> = None;
+    let mut after_blank = false;
+    let mut in_html = false;
+    for line in markdown.split_inclusive('\n') {
+        let trimmed = line.trim();
+        if fence.is_none() && after_blank && offset > start && !in_html
+            && !line.starts_with(char::is_whitespace) && !starts_list_item(line) {
+            blocks.push(&markdown[start..offset]);
+            start = offset;
+        }
+        in_html |= fence.is_none() && line.starts_with('<');
+
+        fence = next_fence(fence, trimmed);
+        after_blank = trimmed.is_empty();
+        offset += line.len();
+    }
+    if start < markdown.len() {
+        blocks.push(&markdown[start..]);
+    }
+    blocks
+}
+
+/// The link reference definitions (`[1]: https://...`) of `markdown` outside code, one
+/// per line.
+pub fn link_definitions(markdown: &str) -> String {
+    let mut definitions = Vec::new();
+    let mut fence: Option<&str> = None;
+    // Definitions can't interrupt a paragraph, but may follow one another.
+    let mut may_define = true;
+    for line in markdown.lines() {
+        let trimmed = line.trim();
+        let indent = line.len() - line.trim_start().len();
+        let is_definition = fence.is_none() && may_define && indent < 4 && is_link_definition(trimmed);
+        if is_definition {
+            definitions.push(trimmed);
+        }
+        fence = next_fence(fence, trimmed);
+        may_define = is_definition || trimmed.is_empty();
+    }
+    definitions.join("\n")
+}
+
+/// Renders one of the [`blocks`] of a text with the text's `definitions`, unless the
+/// block ends inside a code fence that is still being streamed, where they would show
+/// up as code.
+pub fn render_block(block: &str, definitions: &str) -> String {
+    let open_fence = block.lines().fold(None, |fence, line| next_fence(fence, line.trim())).is_some();
+    match definitions.is_empty() || open_fence {
+        true => render(block),
+        false => render(&format!("{block}\n\n{definitions}")),
+    }
+}
+
+fn is_link_definition(line: &str) -> bool {
+    let Some((label, destination)) = line.strip_prefix('[').and_then(|rest| rest.split_once("]:")) else { return false };
+    !label.trim().is_empty() && !label.contains(['[', ']']) && !destination.trim().is_empty()
+}
+
+/// The fence a line leaves open, given the one open before it.
+fn next_fence<'a>(fence: Option<&'a str>, trimmed: &'a str) -> Option<&'a str> {
+    match fence {
+        Some(marker) if trimmed.starts_with(marker) && trimmed.trim_start_matches(&marker[..1]).is_empty() => None,
+        Some(marker) => Some(marker),
+        None => fence_marker(trimmed),
+    }
+}
+
+/// Drops the last line of Markdown that is still being streamed if it is an
+/// unfinished code fence, so ``` `` ``` or ```` ```pyt ```` don't flash up as text or
+/// as a block in the wrong language before the line is complete.
+pub fn without_partial_fence(markdown: &str) -> &str {
+    let line_start = markdown.rfind('\n').map_or(0, |i| i + 1);
+    let line = markdown[line_start..].trim();
+    let is_partial_fence = !line.is_empty()
+        && (line.chars().all(|c| c == '`') || line.chars().all(|c| c == '~') || fence_marker(line).is_some());
+    match is_partial_fence {
+        true => &markdown[..line_start],
+        false => markdown,
+    }
+}
+
+/// The run of backticks or tildes a line opens a code fence with.
+fn fence_marker(line: &str) -> Option<&str> {
+    let c = line.chars().next().filter(|c| matches!(c, '`' | '~'))?;
+    let length = line.len() - line.trim_start_matches(c).len();
+    (length >= 3).then(|| &line[..length])
+}
+
+fn starts_list_item(line: &str) -> bool {
+    let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let marker = match digits {
+        0 => line.strip_prefix(['-', '*', '+']),
+        1..=9 => line[digits..].strip_prefix(['.', ')']),
+        _ => None,
+    };
+    marker.is_some_and(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace))
+}
+
+fn sanitizer() -> &'static ammonia::Builder<'static> {
+    static SANITIZER: OnceLock<ammonia::Builder<'static>> = OnceLock::new();
+    SANITIZER.get_or_init(|| {
+        let mut builder = ammonia::Builder::default();
+        builder
+            // The code block markup written by `highlight`, with only its classes, see
+            // `own_classes`.
+            .add_tags(["button"])
+            .add_tag_attributes("div", ["class", "data-extension"])
+            .add_tag_attributes("span", ["class"])
+            .add_tag_attributes("code", ["class"])
+            .add_tag_attributes("button", ["type", "data-code-action"])
+            // Table column alignment, see `table_alignment`.
+            .add_tag_attributes("th", ["style"])
+            .add_tag_attributes("td", ["style"])
+            // The MathML written by `math`.
+            .add_tags(MATHML_TAGS)
+            .add_tag_attributes("math", ["display"])
+            .add_tag_attributes("mi", ["mathvariant"])
+            .add_tag_attributes("mo", ["accent", "stretchy", "form", "minsize", "maxsize", "mathvariant"])
+            .add_tag_attributes("mfrac", ["linethickness"])
+            .add_tag_attributes("mspace", ["width"])
+            .add_tag_attributes("mstyle", ["displaystyle"])
+            .add_tag_attributes("mtable", ["columnalign"])
+            .attribute_filter(filter_attribute)
+            .link_rel(Some("noopener noreferrer"))
+            // Citations link to the server's own `/api/knowledge/...` URLs.
+            .url_relative(UrlRelative::PassThrough);
+        builder
+    })
+}
+
+/// The MathML elements `latex2mathml` writes.
+const MATHML_TAGS: [&str; 20] = [
+    "math", "mfrac", "mi", "mn", "mo", "mover", "mroot", "mrow", "mspace", "msqrt", "mstyle", "msub",
+    "msubsup", "msup", "mtable", "mtd", "mtext", "mtr", "munder", "munderover",
+];
+
+fn filter_attribute<'a>(element: &str, attribute: &str, value: &'a str) -> Option<Cow<'a, str>> {
+    match attribute {
+        "style" => table_alignment(element, value),
+        "class" => own_classes(element, value),
+        _ => Some(value.into()),
+    }
+}
+
+/// Keeps the `style="text-align: ..."` pulldown-cmark writes on aligned table cells and
+/// drops every other style, which could overlay the page or load remote resources.
+fn table_alignment<'a>(element: &str, value: &'a str) -> Option<Cow<'a, str>> {
+    let is_alignment = matches!(element, "th" | "td")
+        && matches!(value, "text-align: left" | "text-align: center" | "text-align: right");
+    is_alignment.then_some(value.into())
+}
+
+/// Keeps the classes `highlight` writes and drops the rest. The app's own Tailwind
+/// classes in model output could, like styles, cover the page and spoof its UI.
+/// ammonia's `allowed_classes` takes exact names, while highlighting writes a class
+/// per syntect scope, hence the prefixes.
+fn own_classes<'a>(element: &str, value: &'a str) -> Option<Cow<'a, str>> {
+    let is_own = |class: &str| match element {
+        "div" => matches!(class, "code-block" | "code-toolbar"),
+        "span" => class.starts_with("hl-"),
+        "code" => class.starts_with("hl-") || class.starts_with("language-"),
+        _ => false,
+    };
+    let classes: Vec<&str> = value.split_whitespace().filter(|class| is_own(class)).collect();
+    match classes.join(" ") {
+        kept if kept.is_empty() => None,
+        kept if kept == value => Some(value.into()),
+        kept => Some(kept.into()),
+    }
+}
+
+/// Replaces each code block with its highlighted HTML.
+fn highlight_code_blocks<'a>(events: impl Iterator<Item = Event<'a>>) -> impl Iterator<Item = Event<'a>> {
+    let mut code_block: Option<(String, String)> = None;
+    events.filter_map(move |event| match event {
+        Event::Start(Tag::CodeBlock(kind)) => {
+            let language = match kind {
+                CodeBlockKind::Fenced(info) => info.split_whitespace().next().unwrap_or_default().to_string(),
+                CodeBlockKind::Indented => String::new(),
+            };
+            code_block = Some((language, String::new()));
+            None
+        }
+        Event::Text(text) if code_block.is_some() => {
+            if let Some((_, code)) = &mut code_block {
+                code.push_str(&text);
+            }
+            None
+        }
+        Event::End(TagEnd::CodeBlock) => {
+            let (language, code) = code_block.take().unwrap_or_default();
+            Some(Event::Html(highlight(&language, &code).into()))
+        }
+        event => Some(event),
+    })
+}
+
+/// Replaces each math span with its MathML. Math that can't be converted is shown
+/// as its source, like inline code.
+fn render_math<'a>(events: impl Iterator<Item = Event<'a>>) -> impl Iterator<Item = Event<'a>> {
+    events.map(|event| match event {
+        Event::InlineMath(latex) => match math(&latex, DisplayStyle::Inline) {
+            Some(mathml) => Event::InlineHtml(mathml.into()),
+            None => Event::Code(format!("${latex}$").into()),
+        },
+        Event::DisplayMath(latex) => match math(&latex, DisplayStyle::Block) {
+            Some(mathml) => Event::Html(mathml.into()),
+            None => Event::Code(format!("$${latex}$$").into()),
+        },
+        event => event,
+    })
+}
+
+/// LaTeX as MathML, or `None` if it uses anything `latex2mathml` doesn't support.
+fn math(latex: &str, display: DisplayStyle) -> Option<String> {
+    let mathml = latex2mathml::latex_to_mathml(latex, display).ok()?;
+    // Unknown commands don't fail the conversion but end up as `[PARSE ERROR: ...]` text.
+    if mathml.contains("[PARSE ERROR") {
+        return None;
+    }
+    // Operators are written unescaped, e.g. `<mo><</mo>` for `a < b`.
+    Some(mathml
+        .replace("<mo>&</mo>", "<mo>&amp;</mo>")
+        .replace("<mo><</mo>", "<mo>&lt;</mo>")
+        .replace("<mo>></mo>", "<mo>&gt;</mo>"))
+}
+
+fn syntaxes() -> &'static SyntaxSet {
+    static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+/// A code block as `<pre><code>`, like pulldown-cmark writes it, with its tokens in
+/// classed spans and a toolbar above it. Unknown languages are highlighted as plain text.
+fn highlight(language: &str, code: &str) -> String {
+    // The language ends up in a class attribute, so it is kept to characters that can't break out of it.
+    let language: String = language.chars().take_while(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '#' | '.' | '_')).collect();
+    let syntaxes = syntaxes();
+    let syntax = syntaxes.find_syntax_by_token(&language).unwrap_or_else(|| syntaxes.find_syntax_plain_text());
+
+    // Parsing only fails on a broken syntax definition; such blocks are shown unhighlighted.
+    let html = classed_html(syntax, code)
+        .or_else(|_| classed_html(syntaxes.find_syntax_plain_text(), code))
+        .unwrap_or_default();
+
+    let class = match language.is_empty() {
+        true => "hl-code".to_string(),
+        false => format!("hl-code language-{language}"),
+    };
+    // Plain text has no name worth showing, so such blocks are labeled as written.
+    let label = match (syntax.name.as_str(), language.is_empty()) {
+        ("Plain Text", true) => "text",
+        ("Plain Text", false) => language.as_str(),
+        (name, _) => name,
+    };
+    let extension = syntax.file_extensions.first().map_or("txt", String::as_str);
+    format!(
+        "<div class=\"code-block\" data-extension=\"{extension}\">\
+         <div class=\"code-toolbar\"><span>{label}</span><span>\
+         <button type=\"button\" data-code-action=\"copy\">Copy</button>\
+         <button type=\"button\" data-code-action=\"download\">Download</button>\
+         <button type=\"button\" data-code-action=\"wrap\">Wrap</button>\
+         </span></div>\
+         <pre><code class=\"{class}\">{html}</code></pre></div>\n"
+    )
+}
+
+fn classed_html(syntax: &SyntaxReference, code: &str) -> Result<String, syntect::Error> {
+    let mut generator = ClassedHTMLGenerator::new_with_class_style(syntax, syntaxes(), CLASS_STYLE);
+    for line in LinesWithEndings::from(code) {
+        generator.parse_html_for_line_which_includes_newline(line)?;
+    }
+    Ok(generator.finalize())
+}
@@ -0,0 +1,30 @@
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+
+pub const CSV: &str = "text/csv";
+pub const TSV: &str = "text/tab-separated-values";
+
+/// A file uploaded with a prompt. Its extracted text is added to the prompt the
+/// model sees, images are passed to vision models as they are; the original is
+/// served from `/api/attachments/{id}`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Attachment {
+  pub id: Uuid,
+  pub name: String,
+  pub content_type: String,
+  pub size: usize,
+  /// Whether the extracted text was cut to fit the attachment budget.
+  #[serde(default)]
+  pub truncated: bool
+}
+
+impl Attachment {
+  pub fn is_image(&self) -> bool {
+    self.content_type.starts_with("image/")
+  }
+
+  /// CSV and TSV files, which the model can query with the `sql` tool.
+  pub fn is_table(&self) -> bool {
+    self.content_type.starts_with(CSV) || self.content_type.starts_with(TSV)
+  }
+}
@@ -1,11 +1,21 @@
-use serde::{Serialize, Deserialize};
+use crate::model::attachment::Attachment;
+use crate::model::knowledge::Citation;
+use crate::model::tool::ToolStep;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
 use uuid::Uuid;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Conversation {
   pub id: Uuid,
   pub name: String,
-  pub messages: Vec<Message>
+  pub messages: Vec<Message>,
+  /// The persona the conversation was started with; the default one when unset.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub persona_id: Option<Uuid>,
+  /// The knowledge base prompts were last answered from, if any.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub knowledge_base_id: Option<Uuid>
 }
 
 impl Conversation {
@@ -14,6 +24,8 @@ impl Conversation {
       name: name,
       id: Uuid::new_v4(),
       messages: Vec::new(),
+      persona_id: None,
+      knowledge_base_id: None,
     }
   } 
 }
@@ -21,7 +33,144 @@ impl Conversation {
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Message {
   pub id: Uuid,
-  pub role: String,
+  pub role: Role,
   pub content: String,
-  pub timestamp: String
-}
\ No newline at end of file
+  /// Stored as RFC 3339; messages saved before that carry Unix seconds, which are read too.
+  #[serde(deserialize_with = "deserialize_timestamp")]
+  pub timestamp: DateTime<Utc>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub attachments: Vec<Attachment>,
+  /// Knowledge base chunks the answer was given, numbered as it cites them.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub citations: Vec<Citation>,
+  /// Tools the model called before giving the answer, in order.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub tool_steps: Vec<ToolStep>,
+  #[serde(default, skip_serializing_if = "MessageMetadata::is_empty")]
+  pub metadata: MessageMetadata
+}
+
+impl Message {
+  /// A message written now, without attachments or metadata.
+  pub fn new(role: Role, content: String) -> Message {
+    Message {
+      id: Uuid::new_v4(),
+      role,
+      content,
+      timestamp: Utc::now(),
+      attachments: Vec::new(),
+      citations: Vec::new(),
+      tool_steps: Vec::new(),
+      metadata: MessageMetadata::default(),
+    }
+  }
+}
+
+/// Who wrote a message.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+  System,
+  User,
+  Assistant
+}
+
+impl Role {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      Role::System => "system",
+      Role::User => "user",
+      Role::Assistant => "assistant",
+    }
+  }
+
+  /// The role's name as shown next to its messages.
+  pub fn label(self) -> &'static str {
+    match self {
+      Role::System => "System",
+      Role::User => "User",
+      Role::Assistant => "Assistant",
+    }
+  }
+}
+
+/// How an answer came about. Every field is optional: user messages and messages
+/// stored or imported without it have none of them.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct MessageMetadata {
+  /// The model that wrote the answer.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub model: Option<String>,
+  /// Tokens of the prompt the model was given, history included.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub prompt_tokens: Option<u64>,
+  /// Tokens the model generated.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub completion_tokens: Option<u64>,
+  /// Milliseconds from sending the prompt to the first token of the answer.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub time_to_first_token_ms: Option<u64>,
+  /// Milliseconds the model spent generating `completion_tokens`.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub generation_duration_ms: Option<u64>,
+  /// Milliseconds the whole answer took, tool calls included.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub total_duration_ms: Option<u64>,
+  /// Why generation stopped, e.g. `stop` or `length`.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub finish_reason: Option<String>,
+  /// The message this one answers or follows.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub parent_id: Option<Uuid>
+}
+
+impl MessageMetadata {
+  pub fn is_empty(&self) -> bool {
+    *self == MessageMetadata::default()
+  }
+
+  /// How fast the model generated the answer.
+  pub fn tokens_per_second(&self) -> Option<f64> {
+    let tokens = self.completion_tokens? as f64;
+    let seconds = self.generation_duration_ms.filter(|ms| *ms > 0)? as f64 / 1000.0;
+    Some(tokens / seconds)
+  }
+}
+
+fn deserialize_timestamp<'de, D: Deserializer<'de>>(deserializer: D) -> Result<DateTime<Utc>, D::Error> {
+  use serde::de::Error;
+
+  #[derive(Deserialize)]
+  #[serde(untagged)]
+  enum Stored {
+    Seconds(i64),
+    Text(String)
+  }
+
+  let seconds = match Stored::deserialize(deserializer)? {
+    Stored::Seconds(seconds) => seconds,
+    Stored::Text(text) => match text.parse::<i64>() {
+      Ok(seconds) => seconds,
+      Err(_) => return DateTime::parse_from_rfc3339(&text).map(|t| t.with_timezone(&Utc)).map_err(D::Error::custom),
+    },
+  };
+  DateTime::from_timestamp(seconds, 0).ok_or_else(|| D::Error::custom(format!("Timestamp out of range: {seconds}")))
+}
+
+/// Lightweight listing entry for a stored conversation.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ConversationSummary {
+  pub id: Uuid,
+  pub name: String,
+  pub message_count: usize
+}
+
+impl From<&Conversation> for ConversationSummary {
+  fn from(conversation: &Conversation) -> ConversationSummary {
+    ConversationSummary {
+      id: conversation.id,
+      name: conversation.name.clone(),
+      message_count: conversation.messages.len(),
+    }
+  }
+}
@@ -0,0 +1,87 @@
+use crate::model::conversation::MessageMetadata;
+use crate::model::knowledge::Citation;
+use crate::model::tool::ToolStep;
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+
+/// A message from the client, sent either as a WebSocket text frame or as the
+/// body of a `POST /sse` request.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+  Chat(ChatRequest),
+  Command(CommandRequest)
+}
+
+/// A prompt to answer in a conversation.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ChatRequest {
+  pub conversation_id: Uuid,
+  pub content: String,
+  /// Ids of files uploaded to `/api/attachments` to include with the prompt.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub attachments: Vec<Uuid>,
+  /// Persona to start a new conversation with. Ignored once the conversation
+  /// exists, as it keeps the persona it was started with.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub persona_id: Option<Uuid>,
+  /// Knowledge base to retrieve context for the prompt from.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub knowledge_base_id: Option<Uuid>
+}
+
+/// A slash command changing the state of a conversation's session on the server.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CommandRequest {
+  pub conversation_id: Uuid,
+  /// Persona to start the session with if the conversation is new, as for [`ChatRequest`].
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub persona_id: Option<Uuid>,
+  pub command: SessionCommand
+}
+
+/// Session commands. Those taking an optional value report the current one when it is omitted.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "name", rename_all = "snake_case")]
+pub enum SessionCommand {
+  /// Switches the model for the rest of the conversation.
+  Model { model: Option<String> },
+  /// Replaces the system prompt for the rest of the conversation.
+  System { prompt: Option<String> },
+  /// Sets the sampling temperature.
+  Temperature { value: Option<f32> },
+  /// Forgets the conversation's messages, keeping its persona and settings.
+  Clear
+}
+
+/// Events streamed back to the client. The WebSocket sends them as JSON text
+/// frames, the SSE transport as `data:` lines named after [`ChatEvent::name`].
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChatEvent {
+  /// The knowledge base chunks the answer that follows can cite; sent before its tokens.
+  Citations { citations: Vec<Citation> },
+  Token { content: String },
+  /// A tool call the model made and its result, sent once the tool has run.
+  ToolStep { step: ToolStep },
+  /// How the answer was generated; sent once it is complete, before `Done`.
+  Stats { metadata: MessageMetadata },
+  Done,
+  Error { message: String },
+  /// The outcome of a [`SessionCommand`], or a note on how a prompt was handled.
+  Notice { message: String }
+}
+
+impl ChatEvent {
+  pub fn name(&self) -> &'static str {
+    match self {
+      ChatEvent::Citations { .. } => "citations",
+      ChatEvent::Token { .. } => "token",
+      ChatEvent::ToolStep { .. } => "tool_step",
+      ChatEvent::Stats { .. } => "stats",
+      ChatEvent::Done => "done",
+      ChatEvent::Error { .. } => "error",
+      ChatEvent::Notice { .. } => "notice",
+    }
+  }
+}
@@ -0,0 +1,63 @@
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+
+/// A named set of documents whose most relevant chunks are added to prompts.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct KnowledgeBase {
+  pub id: Uuid,
+  pub name: String,
+  /// Server-side directory, relative to `RUSTIC_KNOWLEDGE_DIR`, whose files are indexed on sync.
+  #[serde(default)]
+  pub directory: Option<String>,
+  #[serde(default)]
+  pub documents: Vec<KnowledgeDocument>
+}
+
+impl KnowledgeBase {
+  pub fn new(name: String) -> KnowledgeBase {
+    KnowledgeBase {
+      id: Uuid::new_v4(),
+      name,
+      directory: None,
+      documents: Vec::new(),
+    }
+  }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct KnowledgeDocument {
+  pub id: Uuid,
+  pub name: String,
+  pub size: usize,
+  pub chunks: usize,
+  /// Set for documents read from the knowledge base's directory rather than uploaded.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub path: Option<String>
+}
+
+/// The outcome of syncing a knowledge base with its directory.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SyncReport {
+  pub knowledge_base: KnowledgeBase,
+  /// Files that were empty or had no readable text, relative to the directory.
+  pub skipped: Vec<String>
+}
+
+/// A chunk retrieved for a prompt, which the answer cites as `[number]`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Citation {
+  pub number: usize,
+  pub knowledge_base_id: Uuid,
+  pub document_id: Uuid,
+  pub document_name: String,
+  pub chunk: usize,
+  /// The start of the chunk, shown when hovering the citation.
+  pub excerpt: String
+}
+
+impl Citation {
+  /// Where the full text of the cited chunk is served.
+  pub fn href(&self) -> String {
+    format!("/api/knowledge/{}/documents/{}/chunks/{}", self.knowledge_base_id, self.document_id, self.chunk)
+  }
+}
@@ -1 +1,9 @@
+pub mod attachment;
 pub mod conversation;
+pub mod event;
+pub mod knowledge;
+pub mod persona;
+pub mod search;
+pub mod share;
+pub mod template;
+pub mod tool;
\ No newline at end of file
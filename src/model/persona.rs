@@ -0,0 +1,53 @@
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+
+/// A named preset a conversation is started with: system prompt, few-shot
+/// example turns, and the model and generation options to run it on.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Persona {
+  pub id: Uuid,
+  pub name: String,
+  pub system_prompt: String,
+  #[serde(default)]
+  pub examples: Vec<ExampleTurn>,
+  /// Falls back to `OLLAMA_MODEL_NAME` when unset.
+  #[serde(default)]
+  pub model: Option<String>,
+  #[serde(default)]
+  pub options: GenerationSettings,
+  /// Names of the tools the model may call, from `GET /api/tools`.
+  #[serde(default)]
+  pub tools: Vec<String>
+}
+
+/// One user prompt and the assistant reply it should be answered with.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ExampleTurn {
+  pub user: String,
+  pub assistant: String
+}
+
+/// Generation options of a persona; unset values use the model's defaults.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct GenerationSettings {
+  pub temperature: Option<f32>,
+  pub top_p: Option<f32>,
+  pub top_k: Option<u32>,
+  pub num_ctx: Option<u32>,
+  pub num_predict: Option<i32>,
+  pub seed: Option<i32>
+}
+
+impl Persona {
+  pub fn new(name: String) -> Persona {
+    Persona {
+      id: Uuid::new_v4(),
+      name,
+      system_prompt: String::new(),
+      examples: Vec::new(),
+      model: None,
+      options: GenerationSettings::default(),
+      tools: Vec::new(),
+    }
+  }
+}
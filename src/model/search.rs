@@ -0,0 +1,24 @@
+use crate::model::conversation::Role;
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchMode {
+  #[default]
+  FullText,
+  Semantic
+}
+
+/// A message matching a search, with an HTML snippet whose matches are wrapped in `<mark>`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SearchHit {
+  pub conversation_id: Uuid,
+  pub conversation_name: String,
+  pub message_id: Uuid,
+  pub role: Role,
+  pub timestamp: DateTime<Utc>,
+  pub snippet: String,
+  pub score: f32
+}
@@ -0,0 +1,36 @@
+use crate::model::conversation::Message;
+use serde::{Serialize, Deserialize};
+
+/// Options for a new share link of a conversation.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ShareRequest {
+  pub expires_in_hours: Option<u32>,
+  pub password: Option<String>
+}
+
+/// A share link as listed to the conversation's owner.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ShareSummary {
+  pub token: String,
+  pub created_at: i64,
+  pub expires_at: Option<i64>,
+  pub password_protected: bool
+}
+
+/// The immutable snapshot served at `/s/:token`: the conversation as it was when
+/// shared, without its id, which would give access to the live conversation.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SharedConversation {
+  pub name: String,
+  pub messages: Vec<Message>,
+  pub created_at: i64,
+  pub expires_at: Option<i64>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum ShareView {
+  Available(SharedConversation),
+  PasswordRequired { incorrect: bool },
+  /// Too many wrong passwords were tried; the link can be unlocked again after the wait.
+  TooManyAttempts { retry_after_secs: u64 }
+}
@@ -0,0 +1,75 @@
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Header carrying the random per-browser id that owns private templates.
+pub const CLIENT_ID_HEADER: &str = "X-Client-Id";
+
+/// A reusable prompt whose `{{variable}}` placeholders are filled in before it is sent.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PromptTemplate {
+  pub id: Uuid,
+  pub name: String,
+  pub body: String,
+  /// Shared templates are listed for everyone, private ones only for their owner.
+  #[serde(default)]
+  pub shared: bool,
+  /// Whether the requesting client owns the template and may change it. Set by the server.
+  #[serde(default)]
+  pub editable: bool
+}
+
+impl PromptTemplate {
+  pub fn new(name: String) -> PromptTemplate {
+    PromptTemplate {
+      id: Uuid::new_v4(),
+      name,
+      body: String::new(),
+      shared: false,
+      editable: true,
+    }
+  }
+
+  /// Variable names in order of first appearance.
+  pub fn variables(&self) -> Vec<String> {
+    let mut variables: Vec<String> = Vec::new();
+    for (_, name) in placeholders(&self.body) {
+      if !variables.iter().any(|v| v == name) {
+        variables.push(name.to_string());
+      }
+    }
+    variables
+  }
+
+  /// Replaces every placeholder with its value; unknown variables are left as they are.
+  pub fn render(&self, values: &HashMap<String, String>) -> String {
+    let mut rendered = String::with_capacity(self.body.len());
+    let mut cursor = 0;
+    for ((start, end), name) in placeholders(&self.body) {
+      if let Some(value) = values.get(name) {
+        rendered.push_str(&self.body[cursor..start]);
+        rendered.push_str(value);
+        cursor = end;
+      }
+    }
+    rendered.push_str(&self.body[cursor..]);
+    rendered
+  }
+}
+
+/// The byte range and trimmed name of each `{{ name }}` in `body`.
+fn placeholders(body: &str) -> Vec<((usize, usize), &str)> {
+  let mut found = Vec::new();
+  let mut from = 0;
+  while let Some(open) = body[from..].find("{{").map(|i| from + i) {
+    let Some(close) = body[open + 2..].find("}}").map(|i| open + 2 + i) else { break };
+    let name = body[open + 2..close].trim();
+    if name.is_empty() || name.contains('{') {
+      from = open + 2;
+      continue;
+    }
+    found.push(((open, close + 2), name));
+    from = close + 2;
+  }
+  found
+}
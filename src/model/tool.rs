@@ -0,0 +1,33 @@
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+
+/// A tool the server can run for the model, as listed by `GET /api/tools`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ToolInfo {
+  pub name: String,
+  pub description: String
+}
+
+/// One tool call made while answering, with what the tool returned.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ToolStep {
+  pub name: String,
+  pub arguments: Value,
+  pub result: String,
+  /// Whether the call failed, in which case `result` is the error given to the model.
+  #[serde(default)]
+  pub is_error: bool,
+  /// Rows the tool returned, shown as a table in the chat.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub table: Option<ToolTable>
+}
+
+/// The result of a query: column names and the rows' cells as text, `None` for NULL.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ToolTable {
+  pub columns: Vec<String>,
+  pub rows: Vec<Vec<Option<String>>>,
+  /// Whether rows past the row limit were left out.
+  #[serde(default)]
+  pub truncated: bool
+}
@@ -0,0 +1,67 @@
+//! Slash commands typed into the prompt box.
+
+use rustic_ai::app::commands::{completions, parse, CommandAction};
+use rustic_ai::model::event::SessionCommand;
+
+fn action(input: &str) -> CommandAction {
+    match parse(input) {
+        Some(Ok(action)) => action,
+        Some(Err(e)) => panic!("{input}: {e}"),
+        None => panic!("{input} is not a command"),
+    }
+}
+
+fn error(input: &str) -> String {
+    match parse(input) {
+        Some(Err(e)) => e,
+        _ => panic!("{input} should fail"),
+    }
+}
+
+#[test]
+fn plain_messages_are_not_commands() {
+    assert!(parse("hello /model").is_none());
+    assert!(parse("//model is a path").is_none());
+}
+
+#[test]
+fn unknown_commands_are_errors() {
+    assert_eq!(error("/nope"), "Unknown command /nope. Type /help to list the commands.");
+    assert_eq!(error("/models llama3"), "Unknown command /models. Type /help to list the commands.");
+}
+
+#[test]
+fn arguments_are_trimmed_and_optional() {
+    assert!(matches!(
+        action("/model   llama3.1  "),
+        CommandAction::Session(SessionCommand::Model { model: Some(model) }) if model == "llama3.1"
+    ));
+    assert!(matches!(action("/model"), CommandAction::Session(SessionCommand::Model { model: None })));
+    assert!(matches!(
+        action("/system You are terse.\nAnswer briefly."),
+        CommandAction::Session(SessionCommand::System { prompt: Some(prompt) }) if prompt == "You are terse.\nAnswer briefly."
+    ));
+    assert!(matches!(action("/temp 0.7"), CommandAction::Session(SessionCommand::Temperature { value: Some(value) }) if value == 0.7));
+    assert!(error("/temp hot").starts_with("Temperature must be a number"));
+    assert!(error("/temp 3").starts_with("Temperature must be a number"));
+    assert!(matches!(action("/export"), CommandAction::Export("markdown")));
+    assert!(matches!(action("/export html"), CommandAction::Export("html")));
+    assert_eq!(error("/export pdf"), "Unknown export format `pdf`");
+}
+
+#[test]
+fn stats_can_be_switched_or_toggled() {
+    assert!(matches!(action("/stats on"), CommandAction::Stats(Some(true))));
+    assert!(matches!(action("/stats off"), CommandAction::Stats(Some(false))));
+    assert!(matches!(action("/stats"), CommandAction::Stats(None)));
+    assert_eq!(error("/stats maybe"), "Expected `on` or `off`, got `maybe`");
+}
+
+#[test]
+fn completions_match_the_name_being_typed() {
+    let names = |input| completions(input).iter().map(|command| command.name).collect::<Vec<_>>();
+    assert_eq!(names("/s"), ["system", "stats"]);
+    assert_eq!(names("/"), ["help", "model", "system", "temp", "clear", "export", "stats"]);
+    assert!(names("/model ").is_empty());
+    assert!(names("//").is_empty());
+}
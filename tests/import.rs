@@ -0,0 +1,128 @@
+//! Imported chats are message trees; every root-to-leaf path must become a conversation
+//! of its own, whatever shape the export's tree has.
+
+use rustic_ai::api::import::{self, Source};
+use rustic_ai::model::conversation::Role;
+use serde_json::{json, Value};
+use std::collections::HashSet;
+
+fn node(parent: Option<&str>, children: &[&str], role: &str, text: &str) -> Value {
+    json!({
+        "parent": parent,
+        "children": children,
+        "message": { "author": { "role": role }, "content": { "parts": [text] }, "create_time": 1700000000.0 },
+    })
+}
+
+/// A ChatGPT chat whose answer was regenerated: `root → q → (a1 | a2)`, showing `a2`.
+fn regenerated_chat() -> Value {
+    json!({
+        "conversation_id": "chat-1",
+        "title": "Regex help",
+        "current_node": "a2",
+        "mapping": {
+            "root": { "parent": null, "children": ["q"], "message": null },
+            "q": node(Some("root"), &["a1", "a2"], "user", "How do I match digits?"),
+            "a1": node(Some("q"), &[], "assistant", "Use \\d."),
+            "a2": node(Some("q"), &[], "assistant", "Use [0-9]."),
+        },
+    })
+}
+
+#[test]
+fn mainline_is_the_branch_that_was_shown() {
+    let chat = json!({
+        "conversation_id": "chat-0",
+        "title": "Hello",
+        "current_node": "a",
+        "mapping": {
+            "sys": node(None, &["q"], "system", "You are helpful."),
+            "q": node(Some("sys"), &["a"], "user", "Hi"),
+            "a": node(Some("q"), &[], "assistant", "Hello!"),
+        },
+    });
+    let (conversations, skipped) = import::conversations(&chat, Source::ChatGpt);
+    assert!(skipped.is_empty());
+
+    let [(None, conversation)] = &conversations[..] else { panic!("one conversation: {conversations:?}") };
+    assert_eq!(conversation.name, "Hello");
+    let turns: Vec<_> = conversation.messages.iter().map(|m| (m.role, m.content.as_str())).collect();
+    assert_eq!(turns, [(Role::User, "Hi"), (Role::Assistant, "Hello!")]);
+    assert_eq!(conversation.messages[1].metadata.parent_id, Some(conversation.messages[0].id));
+}
+
+#[test]
+fn branches_become_numbered_conversations() {
+    let (conversations, _) = import::conversations(&regenerated_chat(), Source::ChatGpt);
+
+    // The shown branch is branch 1, so the first other branch is branch 2.
+    let names: Vec<_> = conversations.iter().map(|(branch, c)| (*branch, c.name.as_str())).collect();
+    assert_eq!(names, [(None, "Regex help"), (Some(2), "Regex help (branch 2)")]);
+    assert_eq!(conversations[0].1.messages[1].content, "Use [0-9].");
+    assert_eq!(conversations[1].1.messages[1].content, "Use \\d.");
+}
+
+#[test]
+fn branches_do_not_share_message_ids() {
+    let (conversations, _) = import::conversations(&regenerated_chat(), Source::ChatGpt);
+
+    let ids: Vec<_> = conversations.iter().flat_map(|(_, c)| c.messages.iter().map(|m| m.id)).collect();
+    assert_eq!(ids.len(), 4);
+    assert_eq!(ids.iter().collect::<HashSet<_>>().len(), ids.len());
+
+    // Importing the same export again gives the same ids, so it overwrites.
+    let (again, _) = import::conversations(&regenerated_chat(), Source::ChatGpt);
+    let again: Vec<_> = again.iter().flat_map(|(_, c)| c.messages.iter().map(|m| m.id)).collect();
+    assert_eq!(ids, again);
+}
+
+#[test]
+fn cyclic_trees_are_skipped() {
+    let children_cycle = json!({
+        "conversation_id": "chat-2",
+        "title": "Children cycle",
+        "current_node": "q",
+        "mapping": {
+            "q": node(None, &["a"], "user", "Hi"),
+            "a": node(Some("q"), &["q"], "assistant", "Hello!"),
+        },
+    });
+    let parent_cycle = json!({
+        "conversation_id": "chat-3",
+        "title": "Parent cycle",
+        "mapping": {
+            "q": node(Some("a"), &[], "user", "Hi"),
+            "a": node(Some("q"), &[], "assistant", "Hello!"),
+        },
+    });
+
+    let (conversations, skipped) = import::conversations(&json!([children_cycle, parent_cycle]), Source::ChatGpt);
+    assert!(conversations.is_empty(), "{conversations:?}");
+    let titles: Vec<_> = skipped.iter().map(|s| s.title.as_str()).collect();
+    assert_eq!(titles, ["Children cycle", "Parent cycle"]);
+    assert!(skipped.iter().all(|s| s.reason.contains("cycle")));
+}
+
+#[test]
+fn without_a_usable_current_node_the_longest_branch_is_main() {
+    let mapping = json!({
+        "root": { "parent": null, "children": ["q"], "message": null },
+        "q": node(Some("root"), &["a1", "a2"], "user", "How do I match digits?"),
+        "a1": node(Some("q"), &[], "assistant", "Use \\d."),
+        "a2": node(Some("q"), &["q2"], "assistant", "Use [0-9]."),
+        "q2": node(Some("a2"), &["a3"], "user", "And letters?"),
+        "a3": node(Some("q2"), &[], "assistant", "Use [a-z]."),
+        // Cut off from the root: its parent is not in the export.
+        "orphan": node(Some("gone"), &[], "assistant", "Lost."),
+    });
+
+    for current in [json!(null), json!("missing"), json!("orphan")] {
+        let chat = json!({ "conversation_id": "chat-4", "title": "Regex help", "current_node": current, "mapping": mapping });
+        let (conversations, _) = import::conversations(&chat, Source::ChatGpt);
+
+        let (branch, main) = &conversations[0];
+        assert_eq!(*branch, None, "current_node {current}");
+        assert_eq!(main.messages.last().unwrap().content, "Use [a-z].", "current_node {current}");
+        assert_eq!(conversations.len(), 3);
+    }
+}
@@ -0,0 +1,69 @@
+//! Streamed messages are rendered a block at a time, which must look the same as
+//! rendering them whole.
+
+use rustic_ai::markdown::{blocks, link_definitions, render, render_block, without_partial_fence};
+
+const DOCUMENTS: &[&str] = &[
+    "# Title\n\nA paragraph\nover two lines.\n\nAnother one.",
+    "Intro:\n\n- tight\n- list\n\nAfter the list.",
+    "1. loose\n\n2. list\n\n   with a second paragraph\n\n3. three\n\nDone.",
+    "- item\n\n      indented code in the item\n\n- next",
+    "```rust\nfn main() {\n\n    println!(\"hi\");\n}\n```\n\nText after code.",
+    "~~~\n```\nnot a fence end\n\n~~~\n\nText.",
+    "    indented code\n\n    continues\n\nText.",
+    "> quote\n\n> another quote\n\nText.",
+    "| a | b |\n|---|:-:|\n| 1 | 2 |\n\nBelow the table.",
+    "Some $x^2$ math.\n\n$$\n\\frac{1}{2}\n$$\n\nMore.",
+    "<pre>\nraw\n\nhtml\n</pre>\n\nText.",
+    "Title\n=====\n\n---\n\n* a\n* b\n\n+ c",
+    "See [the docs][docs] and [1].\n\nMore on [1].\n\n[docs]: https://example.com/docs\n[1]: /api/knowledge/1 \"Source\"",
+];
+
+#[test]
+fn blocks_render_like_the_whole() {
+    for document in DOCUMENTS {
+        let parts = blocks(document);
+        assert_eq!(parts.concat(), *document);
+        let definitions = link_definitions(document);
+        let rendered: String = parts.iter().map(|part| render_block(part, &definitions)).collect();
+        assert_eq!(rendered, render(document), "{document:?} split as {parts:?}");
+    }
+}
+
+#[test]
+fn blocks_split_between_paragraphs() {
+    assert_eq!(blocks("a\n\nb\n\n```\nc\n\nd\n```\n"), ["a\n\n", "b\n\n", "```\nc\n\nd\n```\n"]);
+}
+
+#[test]
+fn reference_links_need_their_definitions() {
+    let document = "As shown in [1].\n\n[1]: https://example.com";
+    let first = blocks(document)[0];
+    assert_eq!(first, "As shown in [1].\n\n");
+    assert_eq!(render(first), "<p>As shown in [1].</p>\n");
+    assert_eq!(
+        render_block(first, &link_definitions(document)),
+        "<p>As shown in <a href=\"https://example.com\" rel=\"noopener noreferrer\">1</a>.</p>\n"
+    );
+}
+
+#[test]
+fn link_definitions_are_found_outside_code_and_paragraphs() {
+    let document = "[a]: /a\n[b]: /b 'B'\n\ntext\n[c]: /not-a-definition\n\n```\n[d]: /code\n```\n\n    [e]: /indented\n\n [f]: /f";
+    assert_eq!(link_definitions(document), "[a]: /a\n[b]: /b 'B'\n[f]: /f");
+    assert_eq!(link_definitions("[]: /empty\n\n[x]:\n\n[1] is not one"), "");
+}
+
+#[test]
+fn definitions_are_not_added_to_an_open_fence() {
+    assert_eq!(render_block("```\ncode", "[1]: /one"), render("```\ncode"));
+}
+
+#[test]
+fn partial_fences_are_held_back() {
+    assert_eq!(without_partial_fence("Text\n``"), "Text\n");
+    assert_eq!(without_partial_fence("Text\n```pyt"), "Text\n");
+    assert_eq!(without_partial_fence("```py\nx = 1\n`"), "```py\nx = 1\n");
+    assert_eq!(without_partial_fence("```py\nx = 1\n"), "```py\nx = 1\n");
+    assert_eq!(without_partial_fence("Some `code"), "Some `code");
+}
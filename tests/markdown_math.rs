@@ -0,0 +1,49 @@
+//! `$...$` and `$$...$$` in model output render as MathML, so math needs no script.
+
+use rustic_ai::markdown::render;
+
+#[test]
+fn inline_math_renders_inline() {
+    let html = render("Euler: $e^{i\\pi} + 1 = 0$.");
+    assert!(html.starts_with("<p>Euler: <math display=\"inline\"><msup><mi>e</mi>"), "{html}");
+    assert!(html.contains("<mi>π</mi>"), "{html}");
+    assert!(html.ends_with("</math>.</p>\n"), "{html}");
+}
+
+#[test]
+fn display_math_renders_as_block() {
+    let html = render("$$\n\\sum_{i=1}^n i = \\frac{n(n+1)}{2}\n$$");
+    assert!(html.contains("<math display=\"block\"><munderover><mo>∑</mo>"), "{html}");
+    assert!(html.contains("<mfrac>"), "{html}");
+}
+
+#[test]
+fn matrices_and_fences_render() {
+    let html = render("$$\\left( \\begin{matrix}1 & 2\\\\3 & 4\\end{matrix} \\right)$$");
+    assert!(html.contains("<mo stretchy=\"true\" form=\"prefix\">(</mo>"), "{html}");
+    assert!(html.contains("<mtable><mtr><mtd><mn>1</mn></mtd><mtd><mn>2</mn></mtd></mtr>"), "{html}");
+}
+
+#[test]
+fn operators_are_escaped() {
+    let html = render("$a < b > c$");
+    assert!(html.contains("<mo>&lt;</mo><mi>b</mi><mo>&gt;</mo>"), "{html}");
+}
+
+#[test]
+fn unsupported_math_is_shown_as_source() {
+    assert!(render("$x^$").contains("<code>$x^$</code>"));
+    assert!(render("$$\\unknowncommand{x}$$").contains("<code>$$\\unknowncommand{x}$$</code>"));
+}
+
+#[test]
+fn dollar_amounts_stay_text() {
+    assert_eq!(render("It costs $5, or $6 with tax."), "<p>It costs $5, or $6 with tax.</p>\n");
+}
+
+#[test]
+fn math_in_code_is_left_alone() {
+    let html = render("`$x$`\n\n```\n$$y$$\n```");
+    assert!(html.contains("<code>$x$</code>"), "{html}");
+    assert!(!html.contains("<math"), "{html}");
+}
@@ -0,0 +1,191 @@
+//! Model output is rendered with `inner_html`, so `markdown::render` must never let
+//! script through, whatever HTML or Markdown the model writes.
+
+use rustic_ai::markdown::render;
+
+/// Payloads from common XSS cheat sheets, as raw HTML and as Markdown.
+const CORPUS: &[&str] = &[
+    "<script>alert(1)</script>",
+    "<SCRIPT SRC=https://evil.example/xss.js></SCRIPT>",
+    "<scr<script>ipt>alert(1)</script>",
+    "<<script>script>alert(1)<</script>/script>",
+    "\"><script>alert(1)</script>",
+    "<img src=x onerror=alert(1)>",
+    "<img src=\"x\" ONERROR=\"alert(1)\">",
+    "<img src=x onerror=alert(1)//",
+    "<svg onload=alert(1)>",
+    "<svg><script>alert(1)</script></svg>",
+    "<math><mtext><table><mglyph><style><img src=x onerror=alert(1)>",
+    "<body onload=alert(1)>",
+    "<input autofocus onfocus=alert(1)>",
+    "<details open ontoggle=alert(1)>",
+    "<video><source onerror=alert(1)></video>",
+    "<button data-code-action=copy onclick=alert(1)>Copy</button>",
+    "<div class=\"code-block\" onmouseover=\"alert(1)\">x</div>",
+    "<a href=\"javascript:alert(1)\">x</a>",
+    "<a href=\"JaVaScRiPt:alert(1)\">x</a>",
+    "<a href=\" javascript:alert(1)\">x</a>",
+    "<a href=\"&#106;avascript:alert(1)\">x</a>",
+    "<a href=\"java\tscript:alert(1)\">x</a>",
+    "<a href=\"vbscript:msgbox(1)\">x</a>",
+    "<a href=\"data:text/html,<script>alert(1)</script>\">x</a>",
+    "<iframe src=\"https://evil.example\"></iframe>",
+    "<iframe srcdoc=\"<script>alert(1)</script>\"></iframe>",
+    "<object data=\"javascript:alert(1)\"></object>",
+    "<embed src=\"javascript:alert(1)\">",
+    "<form action=\"javascript:alert(1)\"><button>x</button></form>",
+    "<meta http-equiv=\"refresh\" content=\"0;url=javascript:alert(1)\">",
+    "<base href=\"javascript:alert(1)//\">",
+    "<link rel=stylesheet href=\"https://evil.example/x.css\">",
+    "<style>@import 'https://evil.example/x.css';</style>",
+    "<div style=\"background:url(javascript:alert(1))\">x</div>",
+    "<div style=\"position:fixed;inset:0\">Your session expired</div>",
+    "<div class=\"fixed inset-0 z-50 bg-white\">Your session expired</div>",
+    "<div class=\"code-block fixed inset-0\"><span class=\"hl-code absolute top-0\">x</span></div>",
+    "<code class=\"language-js fixed\">x</code>",
+    "<noscript><p title=\"</noscript><img src=x onerror=alert(1)>\"></noscript>",
+    "<template><script>alert(1)</script></template>",
+    "[x](javascript:alert(1))",
+    "[x](  javascript:alert(1) )",
+    "[x](JAVASCRIPT:alert(1))",
+    "[x](data:text/html;base64,PHNjcmlwdD5hbGVydCgxKTwvc2NyaXB0Pg==)",
+    "![x](javascript:alert(1))",
+    "[x]\n\n[x]: javascript:alert(1)",
+    "<https://example.com/\"onmouseover=\"alert(1)>",
+    "```js\n</code></pre><script>alert(1)</script>\n```",
+    "```\"><script>alert(1)</script>\nx\n```",
+    "`<script>alert(1)</script>`",
+    "| a |\n|:-:|\n| <img src=x onerror=alert(1)> |",
+    "<table><tr><td style=\"text-align: center; background: url(https://evil.example)\">x</td></tr></table>",
+    "<math href=\"javascript:alert(1)\"><mi>x</mi></math>",
+    "<math><maction actiontype=\"statusline\" xlink:href=\"javascript:alert(1)\">x</maction></math>",
+    "<math><mi><svg><script>alert(1)</script></svg></mi></math>",
+    "<math><annotation-xml encoding=\"text/html\"><img src=x onerror=alert(1)></annotation-xml></math>",
+    "$\\text{<script>alert(1)</script>}$",
+    "$$</math><script>alert(1)</script>$$",
+    "$x$<script>alert(1)</script>",
+];
+
+const FORBIDDEN_TAGS: &[&str] = &[
+    "script", "iframe", "object", "embed", "style", "link", "meta", "base", "form", "input", "svg", "noscript",
+    "template", "body", "video", "source", "maction", "annotation-xml",
+];
+
+/// The opening and closing tags of an HTML fragment, lowercased, without `<` and `>`.
+/// Text content can't contain `<` as it is escaped.
+fn tags(html: &str) -> Vec<String> {
+    html.split('<').skip(1).map(|tag| tag.split('>').next().unwrap_or_default().to_lowercase()).collect()
+}
+
+fn assert_safe(input: &str, html: &str) {
+    for tag in tags(html) {
+        let name = tag.trim_start_matches('/').split(|c: char| c.is_whitespace()).next().unwrap_or_default();
+        assert!(!FORBIDDEN_TAGS.contains(&name), "`{input}` rendered a <{name}> tag: {html}");
+
+        let attributes: Vec<&str> = tag.split_whitespace().skip(1).collect();
+        assert!(
+            !attributes.iter().any(|attribute| attribute.starts_with("on")),
+            "`{input}` rendered an event handler: {html}"
+        );
+        assert!(
+            !tag.contains("javascript:") && !tag.contains("vbscript:") && !tag.contains("data:"),
+            "`{input}` rendered a script URL: {html}"
+        );
+        assert!(!tag.contains("srcdoc"), "`{input}` rendered srcdoc: {html}");
+        if let Some(classes) = tag.split("class=\"").nth(1) {
+            let classes = classes.split('"').next().unwrap_or_default();
+            assert!(
+                classes.split_whitespace().all(|class| {
+                    matches!(class, "code-block" | "code-toolbar") || class.starts_with("hl-") || class.starts_with("language-")
+                }),
+                "`{input}` rendered a class of the app: {html}"
+            );
+        }
+        if let Some(style) = tag.split("style=\"").nth(1) {
+            let style = style.split('"').next().unwrap_or_default();
+            assert!(style.starts_with("text-align: ") && !style.contains(';'), "`{input}` rendered a style: {html}");
+        }
+    }
+}
+
+#[test]
+fn corpus_renders_without_script() {
+    for input in CORPUS {
+        assert_safe(input, &render(input));
+    }
+}
+
+#[test]
+fn corpus_inside_markdown_renders_without_script() {
+    // The same payloads in the places model output puts HTML: lists, quotes and paragraphs.
+    for input in CORPUS {
+        for wrapped in [format!("- {input}"), format!("> {input}"), format!("Some text {input} more text\n\n**bold**")] {
+            assert_safe(&wrapped, &render(&wrapped));
+        }
+    }
+}
+
+#[test]
+fn links_open_without_opener() {
+    let html = render("[docs](https://example.com/docs)");
+    assert!(html.contains("href=\"https://example.com/docs\""), "{html}");
+    assert!(html.contains("rel=\"noopener noreferrer\""), "{html}");
+}
+
+#[test]
+fn script_urls_lose_their_href() {
+    for input in ["[x](javascript:alert(1))", "<a href=\"javascript:alert(1)\">x</a>"] {
+        let html = render(input);
+        assert!(!html.contains("href"), "{input}: {html}");
+        assert!(html.contains(">x</a>"), "{input}: {html}");
+    }
+}
+
+#[test]
+fn relative_links_are_kept() {
+    let html = render("See [1].\n\n[1]: /api/knowledge/a/documents/b/chunks/0");
+    assert!(html.contains("href=\"/api/knowledge/a/documents/b/chunks/0\""), "{html}");
+}
+
+#[test]
+fn code_is_escaped_not_dropped() {
+    let html = render("```\n<script>alert(1)</script>\n```\n\nInline `<script>` too.");
+    assert!(html.contains("&lt;script&gt;alert(1)&lt;/script&gt;"), "{html}");
+    assert!(html.contains("<code>&lt;script&gt;</code>"), "{html}");
+    assert!(!html.contains("<script"), "{html}");
+}
+
+#[test]
+fn code_block_toolbar_survives() {
+    let html = render("```rust\nfn main() {}\n```");
+    assert!(html.contains("<div class=\"code-block\" data-extension=\"rs\">"), "{html}");
+    assert!(html.contains("<button type=\"button\" data-code-action=\"copy\">Copy</button>"), "{html}");
+    assert!(html.contains("class=\"hl-code language-rust\""), "{html}");
+    assert!(html.contains("<span class=\"hl-"), "{html}");
+}
+
+#[test]
+fn only_code_block_classes_survive() {
+    let html = render("<div class=\"fixed inset-0 z-50 bg-white\">Your session expired</div>");
+    assert_eq!(html, "<div>Your session expired</div>");
+    let html = render("<div class=\"code-block fixed\"><span class=\"hl-code absolute\">x</span></div>");
+    assert_eq!(html, "<div class=\"code-block\"><span class=\"hl-code\">x</span></div>");
+}
+
+#[test]
+fn table_alignment_survives() {
+    let html = render("| a | b |\n|:-:|--:|\n| 1 | 2 |");
+    assert!(html.contains("<th style=\"text-align: center\">a</th>"), "{html}");
+    assert!(html.contains("<td style=\"text-align: right\">2</td>"), "{html}");
+}
+
+#[test]
+fn ordinary_markdown_is_unchanged() {
+    let html = render("# Title\n\nSome *emphasis*, **strong**, ~~gone~~ and `code`.\n\n1. one\n2. two");
+    assert!(html.contains("<h1>Title</h1>"), "{html}");
+    assert!(html.contains("<em>emphasis</em>"), "{html}");
+    assert!(html.contains("<strong>strong</strong>"), "{html}");
+    assert!(html.contains("<del>gone</del>"), "{html}");
+    assert!(html.contains("<code>code</code>"), "{html}");
+    assert!(html.contains("<ol>\n<li>one</li>"), "{html}");
+}
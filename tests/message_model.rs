@@ -0,0 +1,75 @@
+//! Conversations stored before messages had typed roles, timestamps and metadata must
+//! still load, and messages must survive a round trip through JSON.
+
+use chrono::{DateTime, Utc};
+use rustic_ai::model::conversation::{Conversation, Message, MessageMetadata, Role};
+use uuid::Uuid;
+
+#[test]
+fn previously_stored_messages_load() {
+    let stored = r#"{
+        "id": "6f1c1c5e-1111-4a4a-8a8a-000000000001",
+        "name": "Regex help",
+        "messages": [
+            { "id": "6f1c1c5e-1111-4a4a-8a8a-000000000002", "role": "user", "content": "Hi", "timestamp": "1700000000" },
+            { "id": "6f1c1c5e-1111-4a4a-8a8a-000000000003", "role": "assistant", "content": "Hello", "timestamp": "1700000005" }
+        ]
+    }"#;
+    let conversation: Conversation = serde_json::from_str(stored).unwrap();
+    let [user, assistant] = &conversation.messages[..] else { panic!("two messages") };
+
+    assert_eq!(user.role, Role::User);
+    assert_eq!(assistant.role, Role::Assistant);
+    assert_eq!(user.timestamp, DateTime::from_timestamp(1_700_000_000, 0).unwrap());
+    assert_eq!(assistant.timestamp.to_rfc3339(), "2023-11-14T22:13:25+00:00");
+    assert!(assistant.metadata.is_empty());
+}
+
+#[test]
+fn timestamps_are_read_as_numbers_and_rfc_3339() {
+    for timestamp in ["1700000000", "\"2023-11-14T22:13:20Z\"", "\"2023-11-14T23:13:20+01:00\""] {
+        let json = format!(r#"{{ "id": "{}", "role": "system", "content": "", "timestamp": {timestamp} }}"#, Uuid::nil());
+        let message: Message = serde_json::from_str(&json).unwrap();
+        assert_eq!(message.timestamp, DateTime::from_timestamp(1_700_000_000, 0).unwrap(), "{timestamp}");
+    }
+    let json = format!(r#"{{ "id": "{}", "role": "user", "content": "", "timestamp": "yesterday" }}"#, Uuid::nil());
+    assert!(serde_json::from_str::<Message>(&json).is_err());
+}
+
+#[test]
+fn messages_round_trip() {
+    let prompt = Message::new(Role::User, "Hi".to_string());
+    let answer = Message {
+        metadata: MessageMetadata {
+            model: Some("llama3.1".to_string()),
+            prompt_tokens: Some(12),
+            completion_tokens: Some(34),
+            time_to_first_token_ms: Some(250),
+            generation_duration_ms: Some(1200),
+            total_duration_ms: Some(1800),
+            finish_reason: Some("stop".to_string()),
+            parent_id: Some(prompt.id),
+        },
+        ..Message::new(Role::Assistant, "Hello".to_string())
+    };
+
+    let json = serde_json::to_value(&answer).unwrap();
+    assert_eq!(json["role"], "assistant");
+    assert_eq!(json["metadata"]["parent_id"], prompt.id.to_string());
+    let read: Message = serde_json::from_value(json).unwrap();
+    assert_eq!(read.metadata, answer.metadata);
+    assert_eq!(read.timestamp, answer.timestamp);
+
+    // Messages without metadata are stored as before, apart from the timestamp format.
+    let json = serde_json::to_value(&prompt).unwrap();
+    assert!(json.get("metadata").is_none());
+    assert!(json["timestamp"].as_str().unwrap().parse::<DateTime<Utc>>().is_ok());
+}
+
+#[test]
+fn tokens_per_second_needs_a_generation_time() {
+    let metadata = MessageMetadata { completion_tokens: Some(120), generation_duration_ms: Some(2400), ..MessageMetadata::default() };
+    assert_eq!(metadata.tokens_per_second(), Some(50.0));
+    assert_eq!(MessageMetadata { generation_duration_ms: Some(0), ..metadata.clone() }.tokens_per_second(), None);
+    assert_eq!(MessageMetadata { completion_tokens: None, ..metadata }.tokens_per_second(), None);
+}
@@ -0,0 +1,40 @@
+//! `{{variable}}` placeholders are typed by users, so half-written ones must be left alone.
+
+use rustic_ai::model::template::PromptTemplate;
+use std::collections::HashMap;
+
+fn template(body: &str) -> PromptTemplate {
+    PromptTemplate { body: body.to_string(), ..PromptTemplate::new("Test".to_string()) }
+}
+
+fn values(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+}
+
+#[test]
+fn repeated_variables_are_asked_once_and_filled_everywhere() {
+    let template = template("Translate {{text}} to {{ language }}. Only {{text}}, in {{language}}.");
+    assert_eq!(template.variables(), ["text", "language"]);
+    assert_eq!(
+        template.render(&values(&[("text", "hola"), ("language", "English")])),
+        "Translate hola to English. Only hola, in English."
+    );
+}
+
+#[test]
+fn missing_values_leave_their_placeholder() {
+    let template = template("Dear {{name}}, about {{topic}}:");
+    assert_eq!(template.render(&values(&[("name", "Ada")])), "Dear Ada, about {{topic}}:");
+    assert_eq!(template.render(&HashMap::new()), "Dear {{name}}, about {{topic}}:");
+}
+
+#[test]
+fn unclosed_and_empty_braces_are_text() {
+    let stray = template("{{a}} then {{b and {{ }} and {{c}}");
+    assert_eq!(stray.variables(), ["a", "c"]);
+    assert_eq!(stray.render(&values(&[("a", "1"), ("c", "3")])), "1 then {{b and {{ }} and 3");
+
+    let unclosed = template("Unclosed {{name");
+    assert!(unclosed.variables().is_empty());
+    assert_eq!(unclosed.render(&values(&[("name", "x")])), "Unclosed {{name");
+}
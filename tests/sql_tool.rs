@@ -0,0 +1,79 @@
+//! The `sql` tool runs model-written queries over attached files, so it must only
+//! ever read, and must stop queries that return or run too long.
+
+use rustic_ai::api::store::Store;
+use rustic_ai::api::tools::{ToolCall, ToolRegistry};
+use rustic_ai::model::tool::ToolStep;
+use rustic_ai::model::attachment::{Attachment, CSV};
+use serde_json::json;
+use uuid::Uuid;
+
+/// A registry with the `sql` tool over a `sales.csv` of `rows` rows.
+fn registry(rows: usize) -> ToolRegistry {
+    let store = Store::new(std::env::temp_dir().join(format!("rustic-ai-test-{}", Uuid::new_v4())));
+    let csv = std::iter::once("region,amount".to_string())
+        .chain((0..rows).map(|i| format!("region{},{}", i % 3, i)))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let attachment = Attachment { id: Uuid::new_v4(), name: "sales.csv".to_string(), content_type: CSV.to_string(), size: csv.len(), truncated: false };
+    store.blobs("attachments").put(attachment.id, csv.as_bytes()).unwrap();
+
+    let mut registry = ToolRegistry::with(&[]);
+    registry.add_tables(&store, &[attachment]).unwrap();
+    registry
+}
+
+async fn query(registry: &ToolRegistry, query: &str) -> ToolStep {
+    registry.run(ToolCall { name: "sql".to_string(), arguments: json!({ "query": query }) }).await
+}
+
+#[actix_rt::test]
+async fn queries_read_the_attached_file() {
+    let step = query(&registry(10), "SELECT region, SUM(amount) AS total FROM sales GROUP BY region ORDER BY region").await;
+    assert!(!step.is_error, "{}", step.result);
+    let table = step.table.unwrap();
+    assert_eq!(table.columns, ["region", "total"]);
+    assert_eq!(table.rows[0], [Some("region0".to_string()), Some("18".to_string())]);
+}
+
+#[actix_rt::test]
+async fn writes_are_rejected() {
+    let registry = registry(10);
+    for statement in [
+        "INSERT INTO sales VALUES ('north', 1)",
+        "UPDATE sales SET amount = 0",
+        "DELETE FROM sales",
+        "DROP TABLE sales",
+        "CREATE TABLE stolen (x)",
+        "ATTACH DATABASE 'stolen.db' AS stolen",
+        "PRAGMA query_only = false",
+        "PRAGMA user_version = 5",
+        "PRAGMA writable_schema(1)",
+    ] {
+        let step = query(&registry, statement).await;
+        assert!(step.is_error, "{statement} was allowed: {}", step.result);
+    }
+
+    let step = query(&registry, "SELECT COUNT(*) FROM sales").await;
+    assert_eq!(step.table.unwrap().rows, [[Some("10".to_string())]]);
+
+    // Pragmas that only describe the schema can still be read.
+    let step = query(&registry, "PRAGMA table_info(sales)").await;
+    assert!(!step.is_error, "{}", step.result);
+}
+
+#[actix_rt::test]
+async fn results_are_capped_at_100_rows() {
+    let step = query(&registry(250), "SELECT * FROM sales").await;
+    let table = step.table.unwrap();
+    assert_eq!(table.rows.len(), 100);
+    assert!(table.truncated);
+    assert!(step.result.contains("only the first 100 rows"), "{}", step.result);
+}
+
+#[actix_rt::test]
+async fn endless_queries_are_stopped() {
+    let step = query(&registry(1), "WITH RECURSIVE n(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM n) SELECT COUNT(*) FROM n").await;
+    assert!(step.is_error);
+    assert_eq!(step.result, "The query took longer than 5 seconds and was stopped");
+}
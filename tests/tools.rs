@@ -0,0 +1,94 @@
+//! Tool calls are parsed out of model output, which may be malformed, split across
+//! stream chunks or crafted to hurt the server.
+
+use futures::executor::block_on;
+use rustic_ai::api::tools::{parse_call, CallFilter, ToolCall, ToolRegistry};
+use serde_json::json;
+
+fn calculate(expression: &str) -> Result<String, String> {
+    let call = ToolCall { name: "calculator".to_string(), arguments: json!({ "expression": expression }) };
+    let step = block_on(ToolRegistry::builtin().run(call));
+    match step.is_error {
+        false => Ok(step.result),
+        true => Err(step.result),
+    }
+}
+
+#[test]
+fn calculator_follows_operator_precedence() {
+    assert_eq!(calculate("2 + 3 * 4").as_deref(), Ok("14"));
+    assert_eq!(calculate("(2 + 3) * 4").as_deref(), Ok("20"));
+    assert_eq!(calculate("2 ^ 3 ^ 2").as_deref(), Ok("512"));
+    assert_eq!(calculate("-2 ^ 2").as_deref(), Ok("4"));
+    assert_eq!(calculate("10 - 4 - 3").as_deref(), Ok("3"));
+    assert_eq!(calculate("7 % 4 * 2").as_deref(), Ok("6"));
+    assert_eq!(calculate("sqrt(16) + 1.5e1").as_deref(), Ok("19"));
+}
+
+#[test]
+fn calculator_rejects_division_by_zero() {
+    assert!(calculate("1 / 0").is_err());
+    assert!(calculate("0 / 0").is_err());
+    assert!(calculate("5 % 0").is_err());
+}
+
+#[test]
+fn calculator_rejects_malformed_expressions() {
+    assert_eq!(calculate("(1 + 2").unwrap_err(), "Missing `)`");
+    assert_eq!(calculate("1 + 2)").unwrap_err(), "Unexpected `)`");
+    assert_eq!(calculate("foo(1)").unwrap_err(), "Unknown function `foo`");
+    assert_eq!(calculate("1 +").unwrap_err(), "Unexpected end of expression");
+}
+
+#[test]
+fn calculator_limits_nesting() {
+    assert_eq!(calculate(&format!("{}1{}", "(".repeat(60), ")".repeat(60))).as_deref(), Ok("1"));
+
+    let too_deep = "The expression is nested too deeply";
+    assert_eq!(calculate(&format!("{}1{}", "(".repeat(100_000), ")".repeat(100_000))).unwrap_err(), too_deep);
+    assert_eq!(calculate(&format!("{}1", "-".repeat(100_000))).unwrap_err(), too_deep);
+    assert_eq!(calculate(&format!("2{}", "^2".repeat(100_000))).unwrap_err(), too_deep);
+    assert_eq!(calculate(&format!("{}1{}", "abs(".repeat(100_000), ")".repeat(100_000))).unwrap_err(), too_deep);
+}
+
+#[test]
+fn calls_are_parsed_from_the_end_of_a_reply() {
+    let call = parse_call("Let me compute. <tool_call>{\"name\": \"calculator\", \"arguments\": {\"expression\": \"1+1\"}}</tool_call>").unwrap();
+    assert_eq!(call.name, "calculator");
+    assert_eq!(call.arguments, json!({ "expression": "1+1" }));
+
+    // Models sometimes fence the JSON or stop before the closing tag.
+    let call = parse_call("<tool_call>\n```json\n{\"name\": \"datetime\"}\n```").unwrap();
+    assert_eq!(call.name, "datetime");
+    assert_eq!(call.arguments, json!(null));
+
+    assert!(parse_call("No tools needed.").is_none());
+}
+
+#[test]
+fn malformed_calls_are_reported_to_the_model() {
+    let call = parse_call("<tool_call>{\"name\": \"calculator\", \"arguments\": {</tool_call>").unwrap();
+    assert_eq!(call.name, "");
+    assert!(call.arguments.as_str().unwrap().starts_with("Invalid tool call: "));
+
+    let step = block_on(ToolRegistry::builtin().run(call));
+    assert!(step.is_error);
+}
+
+#[test]
+fn filter_hides_calls_split_across_chunks() {
+    let chunks = ["The answer", " is <to", "ol_", "call>{\"name\": ", "\"calculator\"}</tool_call>"];
+    let mut filter = CallFilter::default();
+    let visible: String = chunks.iter().map(|chunk| filter.push(chunk)).collect();
+    assert_eq!(visible + &filter.finish(), "The answer is ");
+}
+
+#[test]
+fn filter_releases_text_that_is_not_a_call() {
+    let mut filter = CallFilter::default();
+    assert_eq!(filter.push("a <"), "a ");
+    assert_eq!(filter.push("tool"), "");
+    assert_eq!(filter.push("box> b"), "<toolbox> b");
+    assert_eq!(filter.push(" <tool_c"), " ");
+    assert_eq!(filter.finish(), "<tool_c");
+}